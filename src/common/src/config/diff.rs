@@ -0,0 +1,156 @@
+use crate::config::ServiceConfiguration;
+
+/// The per-job differences between two configurations, used to apply a reload incrementally.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigurationDiff {
+    pub added_jobs: Vec<String>,
+    pub changed_jobs: Vec<String>,
+    pub removed_jobs: Vec<String>,
+    pub api_changed: bool,
+}
+
+impl ConfigurationDiff {
+    /// Compares `next` against `previous`, where no previous configuration means everything is new.
+    pub fn between(previous: Option<&ServiceConfiguration>, next: &ServiceConfiguration) -> Self {
+        let Some(previous) = previous else {
+            let mut added_jobs: Vec<_> = next.jobs.keys().cloned().collect();
+            added_jobs.sort();
+            return Self {
+                added_jobs,
+                api_changed: true,
+                ..Self::default()
+            };
+        };
+
+        let mut diff = Self {
+            api_changed: previous.api != next.api,
+            ..Self::default()
+        };
+
+        for (job_id, job) in &next.jobs {
+            match previous.jobs.get(job_id) {
+                None => diff.added_jobs.push(job_id.clone()),
+                Some(previous_job) if previous_job != job => diff.changed_jobs.push(job_id.clone()),
+                Some(_) => {}
+            }
+        }
+
+        for job_id in previous.jobs.keys() {
+            if !next.jobs.contains_key(job_id) {
+                diff.removed_jobs.push(job_id.clone());
+            }
+        }
+
+        diff.added_jobs.sort();
+        diff.changed_jobs.sort();
+        diff.removed_jobs.sort();
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added_jobs.is_empty()
+            && self.changed_jobs.is_empty()
+            && self.removed_jobs.is_empty()
+            && !self.api_changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parser::parse_configuration;
+
+    const BASE: &str = r#"
+        version = 1
+
+        [jobs.a]
+        cron = "0 0 * * *"
+        repository = "C:\\a"
+        password = "secret"
+
+        [jobs.b]
+        cron = "0 1 * * *"
+        repository = "C:\\b"
+        password = "secret"
+    "#;
+
+    #[test]
+    fn when_no_previous_then_everything_added() {
+        let next = parse_configuration(BASE).unwrap();
+
+        let diff = ConfigurationDiff::between(None, &next);
+
+        assert_eq!(diff.added_jobs, vec!["a", "b"]);
+        assert!(diff.api_changed);
+    }
+
+    #[test]
+    fn when_identical_then_empty() {
+        let previous = parse_configuration(BASE).unwrap();
+        let next = parse_configuration(BASE).unwrap();
+
+        let diff = ConfigurationDiff::between(Some(&previous), &next);
+
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn when_job_added_then_only_new_job_reported() {
+        let previous = parse_configuration(BASE).unwrap();
+        let next = parse_configuration(&format!(
+            r#"{BASE}
+            [jobs.c]
+            cron = "0 2 * * *"
+            repository = "C:\\c"
+            password = "secret"
+            "#
+        ))
+        .unwrap();
+
+        let diff = ConfigurationDiff::between(Some(&previous), &next);
+
+        assert_eq!(diff.added_jobs, vec!["c"]);
+        assert!(diff.changed_jobs.is_empty());
+        assert!(diff.removed_jobs.is_empty());
+        assert!(!diff.api_changed);
+    }
+
+    #[test]
+    fn when_job_changed_and_removed_then_reported() {
+        let previous = parse_configuration(BASE).unwrap();
+        let next = parse_configuration(
+            r#"
+            version = 1
+
+            [jobs.a]
+            cron = "0 5 * * *"
+            repository = "C:\\a"
+            password = "secret"
+            "#,
+        )
+        .unwrap();
+
+        let diff = ConfigurationDiff::between(Some(&previous), &next);
+
+        assert_eq!(diff.changed_jobs, vec!["a"]);
+        assert_eq!(diff.removed_jobs, vec!["b"]);
+        assert!(diff.added_jobs.is_empty());
+    }
+
+    #[test]
+    fn when_api_changed_then_reported() {
+        let previous = parse_configuration(BASE).unwrap();
+        let next = parse_configuration(&format!(
+            r#"{BASE}
+            [api]
+            port = 1234
+            "#
+        ))
+        .unwrap();
+
+        let diff = ConfigurationDiff::between(Some(&previous), &next);
+
+        assert!(diff.api_changed);
+        assert!(diff.changed_jobs.is_empty());
+    }
+}
@@ -0,0 +1,159 @@
+use crate::config::FixedDrivesConfiguration;
+use glob::{MatchOptions, Pattern};
+use restic_sdk::retention::ByteSize;
+
+/// A drive found while discovering fixed drives, before filtering.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredDrive {
+    pub mount_point: String,
+    pub file_system: String,
+    pub total_space: u64,
+    pub is_removable: bool,
+}
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: false,
+    require_literal_separator: false,
+    require_literal_leading_dot: false,
+};
+
+impl FixedDrivesConfiguration {
+    /// Whether the drive should be backed up, or the reason it is skipped.
+    pub fn select(&self, drive: &DiscoveredDrive) -> Result<(), String> {
+        if drive.is_removable {
+            return Err("removable".to_owned());
+        }
+
+        if self
+            .exclude
+            .iter()
+            .any(|pattern| matches_mount_point(pattern, &drive.mount_point))
+        {
+            return Err("excluded".to_owned());
+        }
+
+        if !self.file_systems.is_empty()
+            && !self
+                .file_systems
+                .iter()
+                .any(|file_system| file_system.eq_ignore_ascii_case(&drive.file_system))
+        {
+            return Err(format!("file system '{}' not allowed", drive.file_system));
+        }
+
+        if !self.mount_points.is_empty()
+            && !self
+                .mount_points
+                .iter()
+                .any(|pattern| matches_mount_point(pattern, &drive.mount_point))
+        {
+            return Err("mount point not allowed".to_owned());
+        }
+
+        if let Some(min_size) = &self.min_size
+            && let Ok(ByteSize(min_bytes)) = min_size.parse()
+            && drive.total_space < min_bytes
+        {
+            return Err(format!("smaller than {min_size}"));
+        }
+
+        Ok(())
+    }
+}
+
+fn matches_mount_point(pattern: &str, mount_point: &str) -> bool {
+    // Mount points are reported with a trailing separator on Windows ("C:\"), allow patterns without it.
+    let trimmed = mount_point.trim_end_matches(['\\', '/']);
+    Pattern::new(pattern).is_ok_and(|pattern| {
+        pattern.matches_with(mount_point, MATCH_OPTIONS)
+            || (!trimmed.is_empty() && pattern.matches_with(trimmed, MATCH_OPTIONS))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GIGABYTE: u64 = 1024 * 1024 * 1024;
+
+    fn drive(mount_point: &str, file_system: &str, total_space: u64) -> DiscoveredDrive {
+        DiscoveredDrive {
+            mount_point: mount_point.to_owned(),
+            file_system: file_system.to_owned(),
+            total_space,
+            is_removable: false,
+        }
+    }
+
+    #[test]
+    fn when_no_filters_then_every_fixed_drive_selected() {
+        let config = FixedDrivesConfiguration::default();
+
+        assert!(
+            config
+                .select(&drive("C:\\", "NTFS", 500 * GIGABYTE))
+                .is_ok()
+        );
+        assert!(config.select(&drive("/proc", "proc", 0)).is_ok());
+    }
+
+    #[test]
+    fn when_removable_then_rejected() {
+        let config = FixedDrivesConfiguration::default();
+        let drive = DiscoveredDrive {
+            is_removable: true,
+            ..drive("E:\\", "FAT32", 32 * GIGABYTE)
+        };
+
+        assert!(config.select(&drive).is_err());
+    }
+
+    #[test]
+    fn when_file_systems_then_only_matching_selected() {
+        let config = FixedDrivesConfiguration {
+            file_systems: vec!["ntfs".to_owned(), "ReFS".to_owned()],
+            ..FixedDrivesConfiguration::default()
+        };
+
+        assert!(config.select(&drive("C:\\", "NTFS", GIGABYTE)).is_ok());
+        assert!(config.select(&drive("D:\\", "ReFS", GIGABYTE)).is_ok());
+        assert!(config.select(&drive("/run", "tmpfs", GIGABYTE)).is_err());
+    }
+
+    #[test]
+    fn when_mount_points_then_only_matching_selected() {
+        let config = FixedDrivesConfiguration {
+            mount_points: vec!["C:".to_owned(), "/home*".to_owned()],
+            ..FixedDrivesConfiguration::default()
+        };
+
+        assert!(config.select(&drive("C:\\", "NTFS", GIGABYTE)).is_ok());
+        assert!(config.select(&drive("c:\\", "NTFS", GIGABYTE)).is_ok());
+        assert!(config.select(&drive("/home", "ext4", GIGABYTE)).is_ok());
+        assert!(config.select(&drive("D:\\", "NTFS", GIGABYTE)).is_err());
+        assert!(config.select(&drive("/proc", "proc", GIGABYTE)).is_err());
+    }
+
+    #[test]
+    fn when_min_size_then_small_drives_rejected() {
+        let config = FixedDrivesConfiguration {
+            min_size: Some("10G".to_owned()),
+            ..FixedDrivesConfiguration::default()
+        };
+
+        assert!(config.select(&drive("C:\\", "NTFS", 10 * GIGABYTE)).is_ok());
+        assert!(config.select(&drive("R:\\", "NTFS", GIGABYTE)).is_err());
+    }
+
+    #[test]
+    fn when_excluded_then_rejected_even_if_allowed() {
+        let config = FixedDrivesConfiguration {
+            mount_points: vec!["?:\\".to_owned()],
+            exclude: vec!["D:".to_owned()],
+            ..FixedDrivesConfiguration::default()
+        };
+
+        assert!(config.select(&drive("C:\\", "NTFS", GIGABYTE)).is_ok());
+        assert!(config.select(&drive("D:\\", "NTFS", GIGABYTE)).is_err());
+    }
+}
@@ -0,0 +1,72 @@
+/// Checks an exclude pattern for syntax restic would reject, or silently never match.
+///
+/// Patterns may start with `!` to negate an earlier exclude. Character classes (`[a-z]`) must be closed, non-empty
+/// and have ordered ranges, as in Go's `filepath.Match`.
+pub fn validate_exclude_pattern(pattern: &str) -> Result<(), String> {
+    let pattern = pattern.strip_prefix('!').unwrap_or(pattern);
+    if pattern.trim().is_empty() {
+        return Err("the pattern is empty".to_owned());
+    }
+
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c != '[' {
+            continue;
+        }
+
+        let mut class = String::new();
+        let mut closed = false;
+        for c in chars.by_ref() {
+            if c == ']' {
+                closed = true;
+                break;
+            }
+            class.push(c);
+        }
+        if !closed {
+            return Err("a character class is not closed with ']'".to_owned());
+        }
+
+        let class = class.strip_prefix('^').unwrap_or(&class);
+        if class.is_empty() {
+            return Err("a character class is empty".to_owned());
+        }
+
+        let class: Vec<_> = class.chars().collect();
+        for range in class.windows(3) {
+            if let [low, '-', high] = range
+                && low > high
+            {
+                return Err(format!("the character range '{low}-{high}' is reversed"));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn when_patterns_valid_then_ok() {
+        for pattern in [
+            "*.tmp",
+            "C:\\Users\\*\\AppData\\Local\\Temp",
+            "**/node_modules",
+            "!important.tmp",
+            "file[0-9].log",
+            "[^.]*",
+        ] {
+            assert!(validate_exclude_pattern(pattern).is_ok(), "{pattern}");
+        }
+    }
+
+    #[test]
+    fn when_patterns_invalid_then_error() {
+        for pattern in ["", "  ", "!", "file[0-9.log", "file[].log", "file[9-0].log"] {
+            assert!(validate_exclude_pattern(pattern).is_err(), "{pattern}");
+        }
+    }
+}
@@ -0,0 +1,318 @@
+use crate::config::ConfigurationError;
+use glob::{MatchOptions, Pattern};
+use log::debug;
+use std::path::{Component, Path, PathBuf};
+use toml::{Table, Value};
+
+/// Name of the directory next to the main configuration file, whose `*.toml` files are always included.
+pub const CONFIGURATION_DIRECTORY: &str = "conf.d";
+
+/// Everything that makes up a configuration, and should be watched for changes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigurationSources {
+    /// Included files, in the order they are merged.
+    pub files: Vec<PathBuf>,
+    /// Directories that may gain or lose included files.
+    pub directories: Vec<WatchedDirectory>,
+    /// The `conf.d` directory when it does not exist yet, its parent is watched so creating it is picked up.
+    pub missing_configuration_directory: Option<PathBuf>,
+    /// The patterns of included files, to tell changes to them from other files in the watched directories.
+    pub patterns: Vec<Pattern>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WatchedDirectory {
+    pub path: PathBuf,
+    pub recursive: bool,
+}
+
+/// The `include` patterns of the main configuration file.
+pub fn get_include_patterns(document: &Table) -> Result<Vec<String>, ConfigurationError> {
+    match document.get("include") {
+        Some(include) => Ok(include.clone().try_into()?),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Adds the jobs of an included file, job ids must be unique across all files.
+///
+/// Included files may only contain jobs, defaults and templates belong in the main configuration file.
+pub fn merge_included_document(
+    document: &mut Table,
+    included: Table,
+    source: &Path,
+) -> Result<(), ConfigurationError> {
+    let source = source.display().to_string();
+    let Value::Table(jobs) = document
+        .entry("jobs")
+        .or_insert_with(|| Value::Table(Table::new()))
+    else {
+        return Err(ConfigurationError::InvalidConfiguration(
+            "'jobs' must be a table".to_owned(),
+        ));
+    };
+
+    for (key, value) in included {
+        let ("jobs", Value::Table(included_jobs)) = (key.as_str(), value) else {
+            return Err(ConfigurationError::InvalidConfiguration(format!(
+                "'{source}' may only contain [jobs.*] tables, found '{key}'"
+            )));
+        };
+
+        for (job_id, job) in included_jobs {
+            if jobs.contains_key(&job_id) {
+                return Err(ConfigurationError::DuplicateJob(job_id, source));
+            }
+            jobs.insert(job_id, job);
+        }
+    }
+    Ok(())
+}
+
+/// Expands the `include` patterns and the `conf.d` directory, relative to the main configuration file.
+pub fn resolve_configuration_sources(
+    config_path: &Path,
+    include: &[String],
+) -> Result<ConfigurationSources, ConfigurationError> {
+    let base_path = config_path.parent().unwrap_or(Path::new("."));
+    let mut sources = ConfigurationSources::default();
+
+    for pattern in include {
+        let pattern_path = base_path.join(pattern);
+        let pattern_str = pattern_path.to_str().ok_or_else(|| {
+            ConfigurationError::InvalidConfiguration(format!("invalid include pattern '{pattern}'"))
+        })?;
+
+        let entries = glob::glob(pattern_str).map_err(|e| {
+            ConfigurationError::InvalidConfiguration(format!(
+                "invalid include pattern '{pattern}': {e}"
+            ))
+        })?;
+        for entry in entries {
+            let path = entry.map_err(|e| e.into_error())?;
+            sources.add_file(path);
+        }
+
+        sources.add_directory(get_pattern_directory(&pattern_path));
+        sources.add_pattern(&pattern_path);
+    }
+
+    let configuration_directory = base_path.join(CONFIGURATION_DIRECTORY);
+    let pattern_path = configuration_directory.join("*.toml");
+    sources.add_pattern(&pattern_path);
+    if configuration_directory.is_dir() {
+        let entries = glob::glob(pattern_path.to_str().unwrap_or_default())
+            .expect("conf.d pattern should always be valid");
+        for entry in entries {
+            let path = entry.map_err(|e| e.into_error())?;
+            sources.add_file(path);
+        }
+        sources.add_directory(WatchedDirectory {
+            path: configuration_directory,
+            recursive: false,
+        });
+    } else {
+        debug!(
+            "No '{CONFIGURATION_DIRECTORY}' directory found at '{}'.",
+            configuration_directory.display()
+        );
+        sources.missing_configuration_directory = Some(configuration_directory);
+    }
+
+    Ok(sources)
+}
+
+impl ConfigurationSources {
+    fn add_file(&mut self, path: PathBuf) {
+        if path.is_file() && !self.files.contains(&path) {
+            self.files.push(path);
+        }
+    }
+
+    fn add_directory(&mut self, directory: WatchedDirectory) {
+        if directory.path.is_dir() && !self.directories.contains(&directory) {
+            self.directories.push(directory);
+        }
+    }
+
+    fn add_pattern(&mut self, pattern_path: &Path) {
+        // Drops "." components, which the paths of changes do not have.
+        let pattern_path: PathBuf = pattern_path.components().collect();
+        if let Some(pattern) = pattern_path.to_str().and_then(|x| Pattern::new(x).ok()) {
+            self.patterns.push(pattern);
+        }
+    }
+
+    /// Whether the path is an included file, including one that does not exist yet, or a directory holding them.
+    pub fn is_included(&self, path: &Path) -> bool {
+        let options = MatchOptions {
+            case_sensitive: !cfg!(windows),
+            require_literal_separator: true,
+            require_literal_leading_dot: false,
+        };
+        self.patterns
+            .iter()
+            .any(|pattern| pattern.matches_path_with(path, options))
+            || self.directories.iter().any(|x| x.path == path)
+            || self.missing_configuration_directory.as_deref() == Some(path)
+    }
+}
+
+/// The deepest directory of a pattern without glob characters, recursive when `**` is used.
+fn get_pattern_directory(pattern: &Path) -> WatchedDirectory {
+    let mut path = PathBuf::new();
+    let mut recursive = false;
+
+    let mut components = pattern.components().peekable();
+    while let Some(component) = components.next() {
+        let is_glob = match component {
+            Component::Normal(part) => part
+                .to_str()
+                .is_some_and(|part| part.contains(['*', '?', '['])),
+            _ => false,
+        };
+        if is_glob {
+            recursive =
+                pattern.to_str().is_some_and(|x| x.contains("**")) || components.peek().is_some();
+            break;
+        }
+        if components.peek().is_none() {
+            // The last component is a plain file name.
+            break;
+        }
+        path.push(component);
+    }
+
+    WatchedDirectory { path, recursive }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parser::{parse_document, resolve_configuration};
+
+    const JOB: &str = r#"
+        cron = "0 0 * * *"
+        repository = "C:\\a"
+        password = "secret"
+    "#;
+
+    #[test]
+    fn when_included_file_has_jobs_then_merged() {
+        let mut document = parse_document(&format!("version = 1\n[jobs.a]\n{JOB}")).unwrap();
+        let included = parse_document(&format!("[jobs.b]\n{JOB}")).unwrap();
+
+        merge_included_document(&mut document, included, Path::new("b.toml")).unwrap();
+        let config = resolve_configuration(document).unwrap();
+
+        assert_eq!(config.jobs.len(), 2);
+    }
+
+    #[test]
+    fn when_included_job_extends_template_then_resolved() {
+        let mut document =
+            parse_document("version = 1\n[templates.base]\nrepository = \"C:\\\\base\"").unwrap();
+        let included = parse_document(&format!("[jobs.b]\n{JOB}\nextends = \"base\"")).unwrap();
+
+        merge_included_document(&mut document, included, Path::new("b.toml")).unwrap();
+        let config = resolve_configuration(document).unwrap();
+
+        assert_eq!(config.jobs["b"].extends.as_deref(), Some("base"));
+    }
+
+    #[test]
+    fn when_included_job_is_duplicate_then_error() {
+        let mut document = parse_document(&format!("version = 1\n[jobs.a]\n{JOB}")).unwrap();
+        let included = parse_document(&format!("[jobs.a]\n{JOB}")).unwrap();
+
+        let result = merge_included_document(&mut document, included, Path::new("a.toml"));
+
+        assert!(matches!(
+            result,
+            Err(ConfigurationError::DuplicateJob(job_id, _)) if job_id == "a"
+        ));
+    }
+
+    #[test]
+    fn when_included_file_has_other_tables_then_error() {
+        let mut document = parse_document("version = 1").unwrap();
+        let included = parse_document("[api]\nport = 1234").unwrap();
+
+        let result = merge_included_document(&mut document, included, Path::new("a.toml"));
+
+        assert!(matches!(
+            result,
+            Err(ConfigurationError::InvalidConfiguration(_))
+        ));
+    }
+
+    #[test]
+    fn resolves_includes_and_configuration_directory() {
+        let root =
+            std::env::temp_dir().join(format!("restic-service-includes-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("jobs")).unwrap();
+        std::fs::create_dir_all(root.join(CONFIGURATION_DIRECTORY)).unwrap();
+        std::fs::write(root.join("jobs").join("b.toml"), "").unwrap();
+        std::fs::write(root.join("jobs").join("ignored.txt"), "").unwrap();
+        std::fs::write(root.join(CONFIGURATION_DIRECTORY).join("c.toml"), "").unwrap();
+        let document = parse_document("version = 1\ninclude = [\"jobs/*.toml\"]").unwrap();
+        let include = get_include_patterns(&document).unwrap();
+
+        let sources =
+            resolve_configuration_sources(&root.join("service_config.toml"), &include).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            sources.files,
+            vec![
+                root.join("jobs").join("b.toml"),
+                root.join(CONFIGURATION_DIRECTORY).join("c.toml")
+            ]
+        );
+        assert_eq!(sources.directories.len(), 2);
+        assert_eq!(sources.missing_configuration_directory, None);
+    }
+
+    #[test]
+    fn when_configuration_directory_is_missing_then_reported() {
+        let root = std::env::temp_dir().join(format!(
+            "restic-service-missing-conf-d-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&root).unwrap();
+
+        let sources =
+            resolve_configuration_sources(&root.join("service_config.toml"), &[]).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            sources.missing_configuration_directory,
+            Some(root.join(CONFIGURATION_DIRECTORY))
+        );
+        assert!(sources.directories.is_empty());
+    }
+
+    #[test]
+    fn pattern_directory_stops_at_first_glob() {
+        let directory = get_pattern_directory(Path::new("config/jobs/*.toml"));
+
+        assert_eq!(directory.path, PathBuf::from("config/jobs"));
+        assert!(!directory.recursive);
+    }
+
+    #[test]
+    fn pattern_directory_is_recursive_for_nested_globs() {
+        let directory = get_pattern_directory(Path::new("config/**/*.toml"));
+
+        assert_eq!(directory.path, PathBuf::from("config"));
+        assert!(directory.recursive);
+    }
+
+    #[test]
+    fn pattern_directory_of_plain_file_is_parent() {
+        let directory = get_pattern_directory(Path::new("config/jobs.toml"));
+
+        assert_eq!(directory.path, PathBuf::from("config"));
+        assert!(!directory.recursive);
+    }
+}
@@ -0,0 +1,117 @@
+use crate::config::ConfigurationError;
+use std::env;
+
+const ENV_PREFIX: &str = "${env:";
+const SUFFIX: &str = "}";
+
+/// Replaces every `${env:NAME}` in the value with the service's environment variable `NAME`.
+pub fn interpolate_env(value: &str) -> Result<String, ConfigurationError> {
+    interpolate_with(value, |name| env::var(name).ok())
+}
+
+/// Checks the value for malformed `${env:...}` references, without resolving them.
+pub fn validate_interpolation(value: &str) -> Result<(), ConfigurationError> {
+    interpolate_with(value, |_| Some(String::new())).map(|_| ())
+}
+
+fn interpolate_with(
+    value: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<String, ConfigurationError> {
+    let mut result = String::with_capacity(value.len());
+    let mut remaining = value;
+
+    while let Some(start) = remaining.find(ENV_PREFIX) {
+        // The value may be a secret, errors only tell where the reference is.
+        let position = value.len() - remaining.len() + start;
+        result.push_str(&remaining[..start]);
+        let reference = &remaining[start + ENV_PREFIX.len()..];
+
+        let Some(end) = reference.find(SUFFIX) else {
+            return Err(ConfigurationError::InvalidConfiguration(format!(
+                "unterminated environment reference at position {position}"
+            )));
+        };
+
+        let name = &reference[..end];
+        if name.is_empty() {
+            return Err(ConfigurationError::InvalidConfiguration(format!(
+                "empty environment reference at position {position}"
+            )));
+        }
+
+        let Some(resolved) = lookup(name) else {
+            return Err(ConfigurationError::MissingEnvironmentVariable(
+                name.to_owned(),
+            ));
+        };
+        result.push_str(&resolved);
+
+        remaining = &reference[end + SUFFIX.len()..];
+    }
+
+    result.push_str(remaining);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "KEY" => Some("value".to_owned()),
+            "OTHER" => Some("other".to_owned()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn when_no_reference_then_unchanged() {
+        let result = interpolate_with("plain ${value}", lookup).unwrap();
+        assert_eq!(result, "plain ${value}");
+    }
+
+    #[test]
+    fn when_references_then_replaced() {
+        let result = interpolate_with("a-${env:KEY}-${env:OTHER}", lookup).unwrap();
+        assert_eq!(result, "a-value-other");
+    }
+
+    #[test]
+    fn when_variable_missing_then_error() {
+        let result = interpolate_with("${env:MISSING}", lookup);
+        assert!(matches!(
+            result,
+            Err(ConfigurationError::MissingEnvironmentVariable(name)) if name == "MISSING"
+        ));
+    }
+
+    #[test]
+    fn when_unterminated_then_invalid() {
+        let result = validate_interpolation("${env:KEY");
+        assert!(matches!(
+            result,
+            Err(ConfigurationError::InvalidConfiguration(_))
+        ));
+    }
+
+    #[test]
+    fn when_empty_name_then_invalid() {
+        let result = validate_interpolation("${env:}");
+        assert!(matches!(
+            result,
+            Err(ConfigurationError::InvalidConfiguration(_))
+        ));
+    }
+
+    #[test]
+    fn when_invalid_then_value_not_in_error() {
+        let result = validate_interpolation("secret-key-${env:KEY");
+        assert!(matches!(
+            result,
+            Err(ConfigurationError::InvalidConfiguration(message))
+                if message == "unterminated environment reference at position 11"
+        ));
+    }
+}
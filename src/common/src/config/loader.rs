@@ -0,0 +1,29 @@
+use crate::config::{
+    ConfigurationError, ConfigurationSources, ServiceConfiguration, get_include_patterns,
+    merge_included_document, parse_document, resolve_configuration, resolve_configuration_sources,
+};
+use log::debug;
+use std::path::Path;
+use tokio::fs::read_to_string;
+
+/// Reads the main configuration file, merges all included files, then resolves and validates the result.
+///
+/// `on_sources` is called before any included file is read, so a broken include can still be watched.
+pub async fn load_configuration(
+    path: &Path,
+    on_sources: impl FnOnce(&ConfigurationSources),
+) -> Result<ServiceConfiguration, ConfigurationError> {
+    let toml = read_to_string(path).await?;
+    let mut document = parse_document(&toml)?;
+
+    let sources = resolve_configuration_sources(path, &get_include_patterns(&document)?)?;
+    on_sources(&sources);
+
+    for file in &sources.files {
+        debug!("Including configuration file '{}'.", file.display());
+        let toml = read_to_string(file).await?;
+        merge_included_document(&mut document, parse_document(&toml)?, file)?;
+    }
+
+    resolve_configuration(document)
+}
@@ -1,4 +1,5 @@
 use crate::config::watcher::ConfigurationWithWatcher;
+use crate::config::{ServiceConfiguration, load_configuration};
 use log::{debug, warn};
 use std::path::{Path, PathBuf};
 use std::{env, io, path};
@@ -20,12 +21,11 @@ impl ServiceConfigurationManager {
         Ok(ConfigurationWithWatcher::new(config_path))
     }
 
-    // pub async fn read_configuration(&self) -> Result<ServiceConfiguration, ConfigurationError> {
-    //     let config_path = self.locate_configuration_file().await?;
-    //     let toml = read_to_string(config_path).await?;
-    //     let config = parse_configuration(&toml)?;
-    //     Ok(config)
-    // }
+    /// Reads the configuration once, without watching it for changes.
+    pub async fn read_configuration(&self) -> Result<ServiceConfiguration, ConfigurationError> {
+        let config_path = self.locate_configuration_file().await?;
+        load_configuration(Path::new(&config_path), |_| {}).await
+    }
 
     pub async fn locate_configuration_file(&self) -> Result<String, ConfigurationError> {
         let paths = self.get_config_paths();
@@ -81,4 +81,10 @@ pub enum ConfigurationError {
     IoError(#[from] io::Error),
     #[error("Failed to parse configuration: {0}")]
     ParseError(#[from] toml::de::Error),
+    #[error("Invalid configuration: {0}")]
+    InvalidConfiguration(String),
+    #[error("Job '{0}' is defined more than once, again in '{1}'")]
+    DuplicateJob(String, String),
+    #[error("Environment variable '{0}' is not set")]
+    MissingEnvironmentVariable(String),
 }
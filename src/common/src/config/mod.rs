@@ -1,7 +1,25 @@
+mod diff;
+mod drives;
+mod filters;
+mod includes;
+mod interpolation;
+mod loader;
 mod manager;
 mod parser;
+mod sources;
 mod structs;
+mod templates;
 mod watcher;
 
+pub use diff::*;
+pub use drives::*;
+pub use filters::*;
+pub use includes::*;
+pub use interpolation::*;
+pub use loader::*;
 pub use manager::*;
+pub use parser::*;
+pub use sources::*;
 pub use structs::*;
+pub use templates::*;
+pub use watcher::*;
@@ -1,13 +1,290 @@
-use crate::config::ServiceConfiguration;
-use toml::de::Error;
+use crate::config::{
+    BackupJobConfiguration, ConfigurationError, ServiceConfiguration, StatsJobConfiguration,
+    resolve_templates, validate_exclude_pattern, validate_interpolation,
+};
+use chrono::NaiveDateTime;
+use restic_sdk::retention::ByteSize;
+use toml::Table;
 
-pub fn parse_configuration(config: &str) -> Result<ServiceConfiguration, Error> {
-    toml::from_str(config)
+const SUPPORTED_VERSION: u32 = 1;
+const SNAPSHOT_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+const GROUP_BY_FIELDS: [&str; 3] = ["host", "paths", "tags"];
+
+pub fn parse_configuration(config: &str) -> Result<ServiceConfiguration, ConfigurationError> {
+    resolve_configuration(parse_document(config)?)
+}
+
+/// Parses a configuration file as plain TOML, as includes and templates still need to be merged.
+pub fn parse_document(config: &str) -> Result<Table, ConfigurationError> {
+    Ok(toml::from_str(config)?)
+}
+
+/// Applies defaults and templates to the merged document, then deserializes and validates it.
+pub fn resolve_configuration(
+    mut document: Table,
+) -> Result<ServiceConfiguration, ConfigurationError> {
+    resolve_templates(&mut document)?;
+    let config: ServiceConfiguration = document.try_into()?;
+    validate_configuration(&config)?;
+    Ok(config)
+}
+
+/// Checks the parsed configuration for values that deserialize fine, but cannot be run.
+pub fn validate_configuration(config: &ServiceConfiguration) -> Result<(), ConfigurationError> {
+    if config.version != SUPPORTED_VERSION {
+        return Err(ConfigurationError::InvalidConfiguration(format!(
+            "unsupported configuration version {}, expected {SUPPORTED_VERSION}",
+            config.version
+        )));
+    }
+
+    for origin in &config.api.allowed_origins {
+        if !is_origin(origin) {
+            return Err(ConfigurationError::InvalidConfiguration(format!(
+                "invalid API origin '{origin}', expected a scheme and host like 'http://localhost:1420'"
+            )));
+        }
+    }
+
+    for (job_id, job) in &config.jobs {
+        if job.cron.trim().is_empty() {
+            return Err(ConfigurationError::InvalidConfiguration(format!(
+                "job '{job_id}' has an empty cron expression"
+            )));
+        }
+        if job.repository.trim().is_empty() {
+            return Err(ConfigurationError::InvalidConfiguration(format!(
+                "job '{job_id}' has an empty repository"
+            )));
+        }
+        if job.password_source().is_none() {
+            return Err(ConfigurationError::InvalidConfiguration(format!(
+                "job '{job_id}' must set exactly one of password, password_file, password_command or password_env"
+            )));
+        }
+        for (key, value) in &job.environment {
+            validate_interpolation(value).map_err(|e| match e {
+                ConfigurationError::InvalidConfiguration(message) => {
+                    ConfigurationError::InvalidConfiguration(format!(
+                        "job '{job_id}' has an invalid environment value '{key}': {message}"
+                    ))
+                }
+                e => e,
+            })?;
+        }
+        validate_backup(job_id, &job.backup)?;
+        if job.prune.enabled
+            && job
+                .prune
+                .cron
+                .as_ref()
+                .is_none_or(|cron| cron.trim().is_empty())
+        {
+            return Err(ConfigurationError::InvalidConfiguration(format!(
+                "job '{job_id}' has prune enabled without a cron expression"
+            )));
+        }
+        validate_stats(job_id, &job.stats)?;
+        let pin_tag = &job.forget_and_purge.pin_tag;
+        if pin_tag.contains(',') || (!pin_tag.is_empty() && pin_tag.trim().is_empty()) {
+            return Err(ConfigurationError::InvalidConfiguration(format!(
+                "job '{job_id}' has an invalid pin_tag '{pin_tag}', it must not be blank or contain commas"
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether the value is an origin as browsers send it, a scheme and host with an optional port, without a path.
+fn is_origin(value: &str) -> bool {
+    match value.split_once("://") {
+        Some((scheme, host)) => {
+            !scheme.is_empty()
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+                && !host.is_empty()
+                && !host.contains(['/', '*', ' '])
+        }
+        None => false,
+    }
+}
+
+fn validate_stats(job_id: &str, stats: &StatsJobConfiguration) -> Result<(), ConfigurationError> {
+    if !stats.enabled {
+        return Ok(());
+    }
+    if stats
+        .cron
+        .as_ref()
+        .is_none_or(|cron| cron.trim().is_empty())
+    {
+        return Err(ConfigurationError::InvalidConfiguration(format!(
+            "job '{job_id}' has stats enabled without a cron expression"
+        )));
+    }
+    if stats.modes.is_empty() {
+        return Err(ConfigurationError::InvalidConfiguration(format!(
+            "job '{job_id}' has stats enabled without any mode"
+        )));
+    }
+    if stats.hosts.iter().any(|host| host.trim().is_empty()) {
+        return Err(ConfigurationError::InvalidConfiguration(format!(
+            "job '{job_id}' has an empty stats host"
+        )));
+    }
+    Ok(())
+}
+
+fn validate_backup(
+    job_id: &str,
+    backup: &BackupJobConfiguration,
+) -> Result<(), ConfigurationError> {
+    for tag in &backup.tags {
+        if tag.trim().is_empty() || tag.contains(',') {
+            return Err(ConfigurationError::InvalidConfiguration(format!(
+                "job '{job_id}' has an invalid tag '{tag}', tags must not be empty or contain commas"
+            )));
+        }
+    }
+
+    let patterns = backup.exclude.iter().chain(&backup.iexclude);
+    for pattern in patterns {
+        validate_exclude_pattern(pattern).map_err(|reason| {
+            ConfigurationError::InvalidConfiguration(format!(
+                "job '{job_id}' has an invalid exclude pattern '{pattern}', {reason}"
+            ))
+        })?;
+    }
+
+    if backup
+        .sources
+        .iter()
+        .any(|source| source.path().trim().is_empty())
+    {
+        return Err(ConfigurationError::InvalidConfiguration(format!(
+            "job '{job_id}' has an empty source"
+        )));
+    }
+
+    let files = [
+        ("exclude_file", &backup.exclude_file),
+        ("iexclude_file", &backup.iexclude_file),
+        ("exclude_if_present", &backup.exclude_if_present),
+        ("files_from", &backup.files_from),
+        ("files_from_verbatim", &backup.files_from_verbatim),
+        ("files_from_raw", &backup.files_from_raw),
+    ];
+    for (name, files) in files {
+        if files.iter().any(|file| file.trim().is_empty()) {
+            return Err(ConfigurationError::InvalidConfiguration(format!(
+                "job '{job_id}' has an empty entry in {name}"
+            )));
+        }
+    }
+
+    if let Some(program) = backup.stdin_command.first() {
+        if program.trim().is_empty() {
+            return Err(ConfigurationError::InvalidConfiguration(format!(
+                "job '{job_id}' has an empty stdin_command program"
+            )));
+        }
+        if !backup.sources.is_empty()
+            || backup.source_fixed_drives
+            || !backup.files_from.is_empty()
+            || !backup.files_from_verbatim.is_empty()
+            || !backup.files_from_raw.is_empty()
+        {
+            return Err(ConfigurationError::InvalidConfiguration(format!(
+                "job '{job_id}' has a stdin_command, it cannot also have sources, source_fixed_drives or files_from"
+            )));
+        }
+    } else if backup.stdin_filename.is_some() {
+        return Err(ConfigurationError::InvalidConfiguration(format!(
+            "job '{job_id}' has a stdin_filename without a stdin_command"
+        )));
+    }
+    if backup
+        .stdin_filename
+        .as_ref()
+        .is_some_and(|filename| filename.trim().is_empty())
+    {
+        return Err(ConfigurationError::InvalidConfiguration(format!(
+            "job '{job_id}' has an empty stdin_filename"
+        )));
+    }
+
+    if let Some(size) = &backup.exclude_larger_than {
+        size.parse::<ByteSize>().map_err(|e| {
+            ConfigurationError::InvalidConfiguration(format!(
+                "job '{job_id}' has an invalid exclude_larger_than: {e}"
+            ))
+        })?;
+    }
+
+    let drives = &backup.fixed_drives;
+    for pattern in drives.mount_points.iter().chain(&drives.exclude) {
+        glob::Pattern::new(pattern).map_err(|e| {
+            ConfigurationError::InvalidConfiguration(format!(
+                "job '{job_id}' has an invalid fixed drive pattern '{pattern}', {e}"
+            ))
+        })?;
+    }
+    if let Some(size) = &drives.min_size {
+        size.parse::<ByteSize>().map_err(|e| {
+            ConfigurationError::InvalidConfiguration(format!(
+                "job '{job_id}' has an invalid fixed drive min_size: {e}"
+            ))
+        })?;
+    }
+
+    if let Some(time) = &backup.time
+        && NaiveDateTime::parse_from_str(time, SNAPSHOT_TIME_FORMAT).is_err()
+    {
+        return Err(ConfigurationError::InvalidConfiguration(format!(
+            "job '{job_id}' has an invalid backup time '{time}', expected the format 'YYYY-MM-DD hh:mm:ss'"
+        )));
+    }
+
+    if backup
+        .parent
+        .as_ref()
+        .is_some_and(|parent| parent.trim().is_empty())
+    {
+        return Err(ConfigurationError::InvalidConfiguration(format!(
+            "job '{job_id}' has an empty parent snapshot"
+        )));
+    }
+
+    if backup.read_concurrency == Some(0) {
+        return Err(ConfigurationError::InvalidConfiguration(format!(
+            "job '{job_id}' has a read_concurrency of 0, it must be at least 1"
+        )));
+    }
+
+    if let Some(group_by) = &backup.group_by
+        && !group_by.is_empty()
+        && group_by
+            .split(',')
+            .any(|field| !GROUP_BY_FIELDS.contains(&field.trim()))
+    {
+        return Err(ConfigurationError::InvalidConfiguration(format!(
+            "job '{job_id}' has an invalid group_by '{group_by}', expected any of {} separated by comma",
+            GROUP_BY_FIELDS.join(", ")
+        )));
+    }
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{
+        ForgetConfiguration, ManifestFormat, MissingSourcePolicy, PasswordSource, StatsMode,
+        TagFilter,
+    };
 
     #[test]
     fn can_handle_empty_config() {
@@ -37,4 +314,526 @@ mod tests {
         assert_eq!(result.jobs.len(), 1);
         assert_eq!(result.jobs["job1"].cron, "0 0 * * *");
     }
+
+    #[test]
+    fn can_parse_documented_example() {
+        let config = include_str!("../../../../docs/service_config.toml");
+
+        let result: ServiceConfiguration = parse_configuration(config).unwrap();
+
+        assert_eq!(result.jobs.len(), 2);
+    }
+
+    #[test]
+    fn when_version_unsupported_then_invalid() {
+        let config = r#"
+            version = 2
+        "#;
+
+        let result = parse_configuration(config);
+
+        assert!(matches!(
+            result,
+            Err(ConfigurationError::InvalidConfiguration(_))
+        ));
+    }
+
+    #[test]
+    fn when_password_file_then_valid() {
+        let config = r#"
+            version = 1
+
+            [jobs.job1]
+            cron = "0 0 * * *"
+            repository = "C:\\Some Path\\"
+            password_file = "C:\\secret.txt"
+        "#;
+
+        let result: ServiceConfiguration = parse_configuration(config).unwrap();
+
+        assert_eq!(
+            result.jobs["job1"].password_source(),
+            Some(PasswordSource::File("C:\\secret.txt".to_owned()))
+        );
+    }
+
+    #[test]
+    fn when_no_password_source_then_invalid() {
+        let config = r#"
+            version = 1
+
+            [jobs.job1]
+            cron = "0 0 * * *"
+            repository = "C:\\Some Path\\"
+        "#;
+
+        let result = parse_configuration(config);
+
+        assert!(matches!(
+            result,
+            Err(ConfigurationError::InvalidConfiguration(_))
+        ));
+    }
+
+    #[test]
+    fn when_multiple_password_sources_then_invalid() {
+        let config = r#"
+            version = 1
+
+            [jobs.job1]
+            cron = "0 0 * * *"
+            repository = "C:\\Some Path\\"
+            password = "secret"
+            password_env = "RESTIC_SECRET"
+        "#;
+
+        let result = parse_configuration(config);
+
+        assert!(matches!(
+            result,
+            Err(ConfigurationError::InvalidConfiguration(_))
+        ));
+    }
+
+    #[test]
+    fn when_environment_reference_malformed_then_invalid() {
+        let config = r#"
+            version = 1
+
+            [jobs.job1]
+            cron = "0 0 * * *"
+            repository = "C:\\Some Path\\"
+            password = "secret"
+
+            [jobs.job1.environment]
+            AWS_SECRET_ACCESS_KEY = "${env:AWS_SECRET"
+        "#;
+
+        let result = parse_configuration(config);
+
+        assert!(matches!(
+            result,
+            Err(ConfigurationError::InvalidConfiguration(message))
+                if message.contains("'AWS_SECRET_ACCESS_KEY'") && !message.contains("${env:")
+        ));
+    }
+
+    #[test]
+    fn when_repository_empty_then_invalid() {
+        let config = r#"
+            version = 1
+
+            [jobs.job1]
+            cron = "0 0 * * *"
+            repository = ""
+            password = "secret"
+        "#;
+
+        let result = parse_configuration(config);
+
+        assert!(matches!(
+            result,
+            Err(ConfigurationError::InvalidConfiguration(_))
+        ));
+    }
+
+    #[test]
+    fn when_backup_labels_then_valid() {
+        let config = r#"
+            version = 1
+
+            [jobs.job1]
+            cron = "0 0 * * *"
+            repository = "C:\\Some Path\\"
+            password = "secret"
+
+            [jobs.job1.backup]
+            tags = ["nightly"]
+            host = "workstation"
+            time = "2024-01-02 03:04:05"
+            group_by = "host,tags"
+        "#;
+
+        let result: ServiceConfiguration = parse_configuration(config).unwrap();
+
+        assert_eq!(result.jobs["job1"].backup.tags, vec!["nightly"]);
+        assert_eq!(
+            result.jobs["job1"].backup.host.as_deref(),
+            Some("workstation")
+        );
+    }
+
+    #[test]
+    fn when_backup_tag_has_comma_then_invalid() {
+        let result = parse_configuration(&backup_config(r#"tags = ["a,b"]"#));
+
+        assert!(matches!(
+            result,
+            Err(ConfigurationError::InvalidConfiguration(_))
+        ));
+    }
+
+    #[test]
+    fn when_backup_time_malformed_then_invalid() {
+        let result = parse_configuration(&backup_config(r#"time = "2024-01-02T03:04:05""#));
+
+        assert!(matches!(
+            result,
+            Err(ConfigurationError::InvalidConfiguration(_))
+        ));
+    }
+
+    #[test]
+    fn when_backup_group_by_unknown_then_invalid() {
+        let result = parse_configuration(&backup_config(r#"group_by = "host,path""#));
+
+        assert!(matches!(
+            result,
+            Err(ConfigurationError::InvalidConfiguration(_))
+        ));
+    }
+
+    fn backup_config(backup: &str) -> String {
+        format!(
+            r#"
+            version = 1
+
+            [jobs.job1]
+            cron = "0 0 * * *"
+            repository = "C:\\Some Path\\"
+            password = "secret"
+
+            [jobs.job1.backup]
+            {backup}
+            "#
+        )
+    }
+
+    #[test]
+    fn when_backup_filters_then_valid() {
+        let result = parse_configuration(&backup_config(
+            r#"
+            exclude = ["*.tmp", "!keep.tmp"]
+            iexclude = ["thumbs.db"]
+            exclude_if_present = [".nobackup"]
+            exclude_larger_than = "2G"
+            files_from = ["C:\\backup-list.txt"]
+            "#,
+        ))
+        .unwrap();
+
+        assert_eq!(
+            result.jobs["job1"].backup.exclude,
+            vec!["*.tmp", "!keep.tmp"]
+        );
+    }
+
+    #[test]
+    fn when_exclude_pattern_malformed_then_invalid() {
+        let result = parse_configuration(&backup_config(r#"exclude = ["file[0-9.log"]"#));
+
+        assert!(matches!(
+            result,
+            Err(ConfigurationError::InvalidConfiguration(_))
+        ));
+    }
+
+    #[test]
+    fn when_exclude_larger_than_malformed_then_invalid() {
+        let result = parse_configuration(&backup_config(r#"exclude_larger_than = "2 GB""#));
+
+        assert!(matches!(
+            result,
+            Err(ConfigurationError::InvalidConfiguration(_))
+        ));
+    }
+
+    #[test]
+    fn when_sources_mixed_then_valid() {
+        let result = parse_configuration(&backup_config(
+            r#"
+            sources = ["C:\\Users", { path = "D:\\Data", required = true }]
+            missing_sources = "fail"
+            "#,
+        ))
+        .unwrap();
+
+        let backup = &result.jobs["job1"].backup;
+        assert_eq!(backup.missing_sources, MissingSourcePolicy::Fail);
+
+        let sources = &backup.sources;
+        assert_eq!(sources[0].path(), "C:\\Users");
+        assert!(!sources[0].is_required());
+        assert_eq!(sources[1].path(), "D:\\Data");
+        assert!(sources[1].is_required());
+    }
+
+    #[test]
+    fn when_fixed_drive_min_size_malformed_then_invalid() {
+        let result = parse_configuration(&backup_config(
+            r#"
+            source_fixed_drives = true
+            fixed_drives = { min_size = "ten gigabytes" }
+            "#,
+        ));
+
+        assert!(matches!(
+            result,
+            Err(ConfigurationError::InvalidConfiguration(_))
+        ));
+    }
+
+    #[test]
+    fn when_stdin_command_then_valid() {
+        let result = parse_configuration(&backup_config(
+            r#"
+            stdin_command = ["pg_dump", "-U", "postgres", "app"]
+            stdin_filename = "app.sql"
+            "#,
+        ))
+        .unwrap();
+
+        let backup = &result.jobs["job1"].backup;
+        assert_eq!(
+            backup.stdin_command,
+            vec!["pg_dump", "-U", "postgres", "app"]
+        );
+        assert_eq!(backup.stdin_filename.as_deref(), Some("app.sql"));
+    }
+
+    #[test]
+    fn when_stdin_command_with_sources_then_invalid() {
+        let result = parse_configuration(&backup_config(
+            r#"
+            stdin_command = ["pg_dump", "app"]
+            sources = ["C:\\Users"]
+            "#,
+        ));
+
+        assert!(matches!(
+            result,
+            Err(ConfigurationError::InvalidConfiguration(_))
+        ));
+    }
+
+    #[test]
+    fn when_stdin_filename_without_command_then_invalid() {
+        let result = parse_configuration(&backup_config(r#"stdin_filename = "app.sql""#));
+
+        assert!(matches!(
+            result,
+            Err(ConfigurationError::InvalidConfiguration(_))
+        ));
+    }
+
+    #[test]
+    fn when_read_concurrency_zero_then_invalid() {
+        let result = parse_configuration(&backup_config("read_concurrency = 0"));
+
+        assert!(matches!(
+            result,
+            Err(ConfigurationError::InvalidConfiguration(_))
+        ));
+    }
+
+    #[test]
+    fn when_manifest_then_parsed() {
+        let result = parse_configuration(&backup_config(r#"manifest = "gzip""#)).unwrap();
+
+        assert_eq!(
+            result.jobs["job1"].backup.manifest,
+            Some(ManifestFormat::Gzip)
+        );
+    }
+
+    #[test]
+    fn when_prune_enabled_without_cron_then_invalid() {
+        let result = parse_configuration(
+            r#"
+            version = 1
+
+            [jobs.job1]
+            cron = "0 0 * * *"
+            repository = "C:\\Some Path\\"
+            password = "secret"
+
+            [jobs.job1.prune]
+            enabled = true
+            "#,
+        );
+
+        assert!(matches!(
+            result,
+            Err(ConfigurationError::InvalidConfiguration(_))
+        ));
+    }
+
+    #[test]
+    fn when_stats_enabled_then_default_modes() {
+        let config = parse_configuration(
+            r#"
+            version = 1
+
+            [jobs.job1]
+            cron = "0 0 * * *"
+            repository = "C:\\Some Path\\"
+            password = "secret"
+
+            [jobs.job1.stats]
+            enabled = true
+            cron = "0 6 * * *"
+            hosts = ["laptop"]
+            "#,
+        )
+        .unwrap();
+
+        let stats = &config.jobs["job1"].stats;
+        assert_eq!(
+            stats.modes,
+            vec![StatsMode::RestoreSize, StatsMode::RawData]
+        );
+        assert_eq!(stats.hosts, vec!["laptop"]);
+    }
+
+    #[test]
+    fn when_stats_without_modes_then_invalid() {
+        let result = parse_configuration(
+            r#"
+            version = 1
+
+            [jobs.job1]
+            cron = "0 0 * * *"
+            repository = "C:\\Some Path\\"
+            password = "secret"
+
+            [jobs.job1.stats]
+            enabled = true
+            cron = "0 6 * * *"
+            modes = []
+            "#,
+        );
+
+        assert!(matches!(
+            result,
+            Err(ConfigurationError::InvalidConfiguration(_))
+        ));
+    }
+
+    #[test]
+    fn when_no_api_origins_then_tray_ui_allowed() {
+        let config = parse_configuration("version = 1").unwrap();
+
+        assert_eq!(
+            config.api.allowed_origins[..2],
+            ["http://tauri.localhost", "tauri://localhost"]
+        );
+        // The dev server of the tray UI, for `tauri dev`.
+        assert_eq!(
+            config
+                .api
+                .allowed_origins
+                .contains(&"http://localhost:1420".to_owned()),
+            cfg!(debug_assertions)
+        );
+    }
+
+    #[test]
+    fn when_api_origins_then_validated() {
+        let valid = parse_configuration(
+            r#"
+            version = 1
+
+            [api]
+            allowed_origins = ["http://localhost:1420"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(valid.api.allowed_origins, vec!["http://localhost:1420"]);
+
+        for origin in ["*", "localhost:1420", "http://", "http://localhost/ui"] {
+            let result = parse_configuration(&format!(
+                "version = 1\n[api]\nallowed_origins = [\"{origin}\"]"
+            ));
+
+            assert!(
+                matches!(result, Err(ConfigurationError::InvalidConfiguration(_))),
+                "{origin} should be invalid"
+            );
+        }
+    }
+
+    #[test]
+    fn when_forget_then_default_pin_tag() {
+        let config = parse_configuration(&backup_config("")).unwrap();
+
+        assert_eq!(config.jobs["job1"].forget_and_purge.pin_tag, "pinned");
+    }
+
+    #[test]
+    fn when_forget_tag_not_set_then_filtered_by_job_tag_and_untagged() {
+        let config = parse_configuration(&backup_config("")).unwrap();
+
+        assert_eq!(
+            config.jobs["job1"].forget_and_purge.get_tag_filters("job1"),
+            vec!["job:job1", ""]
+        );
+    }
+
+    #[test]
+    fn when_forget_tag_set_then_filtered_by_it() {
+        let forget = ForgetConfiguration {
+            tag: Some(TagFilter::Tag("daily".to_owned())),
+            ..Default::default()
+        };
+
+        assert_eq!(forget.get_tag_filters("job1"), vec!["daily"]);
+    }
+
+    #[test]
+    fn when_forget_tag_list_set_then_filtered_by_any() {
+        let config = parse_configuration(&format!(
+            "{}
+[jobs.job1.forget_and_purge]
+tag = [\"job:job1\", \"daily\"]",
+            backup_config("")
+        ))
+        .unwrap();
+
+        assert_eq!(
+            config.jobs["job1"].forget_and_purge.get_tag_filters("job1"),
+            vec!["job:job1", "daily"]
+        );
+    }
+
+    #[test]
+    fn when_forget_tag_list_empty_then_not_filtered() {
+        let forget = ForgetConfiguration {
+            tag: Some(TagFilter::Tags(Vec::new())),
+            ..Default::default()
+        };
+
+        assert!(forget.get_tag_filters("job1").is_empty());
+    }
+
+    #[test]
+    fn when_pin_tag_with_comma_then_invalid() {
+        let result = parse_configuration(
+            r#"
+            version = 1
+
+            [jobs.job1]
+            cron = "0 0 * * *"
+            repository = "C:\\Some Path\\"
+            password = "secret"
+
+            [jobs.job1.forget_and_purge]
+            pin_tag = "pinned,forever"
+            "#,
+        );
+
+        assert!(matches!(
+            result,
+            Err(ConfigurationError::InvalidConfiguration(_))
+        ));
+    }
 }
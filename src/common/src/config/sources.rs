@@ -0,0 +1,177 @@
+use log::warn;
+use std::env;
+use std::path::Path;
+
+/// Expands a leading `~` and `%NAME%`, `$NAME` or `${NAME}` environment variables in a backup source.
+///
+/// References to variables that are not set are kept as-is, so paths like `C:\$Recycle.Bin` are left alone.
+pub fn expand_source(source: &str) -> String {
+    expand_source_with(source, get_home_directory().as_deref(), |name| {
+        env::var(name).ok()
+    })
+}
+
+/// Whether the source contains glob characters and should be matched against the filesystem.
+pub fn is_glob_pattern(source: &str) -> bool {
+    source.contains(['*', '?', '['])
+}
+
+/// The paths an expanded source refers to. A path that exists is used as is, even when it contains glob characters
+/// (e.g. `D:\Photos [2020]`), anything else with glob characters is matched against the filesystem.
+///
+/// Sources without glob characters are returned whether they exist or not.
+pub fn match_source(source: &str) -> Result<Vec<String>, glob::PatternError> {
+    if !is_glob_pattern(source) || Path::new(source).try_exists().unwrap_or(false) {
+        return Ok(vec![source.to_owned()]);
+    }
+
+    Ok(glob::glob(source)?
+        .filter_map(|path| match path {
+            Ok(path) => path.to_str().map(|path| path.to_owned()),
+            Err(e) => {
+                warn!(
+                    "Failed to read a match of source '{source}', it will be ignored. Error: {e}"
+                );
+                None
+            }
+        })
+        .collect())
+}
+
+fn get_home_directory() -> Option<String> {
+    #[cfg(windows)]
+    let name = "USERPROFILE";
+    #[cfg(not(windows))]
+    let name = "HOME";
+    env::var(name).ok()
+}
+
+fn expand_source_with(
+    source: &str,
+    home: Option<&str>,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> String {
+    let mut result = String::with_capacity(source.len());
+
+    let mut remaining = source;
+    if let Some(home) = home
+        && let Some(rest) = source.strip_prefix('~')
+        && (rest.is_empty() || rest.starts_with(['/', '\\']))
+    {
+        result.push_str(home);
+        remaining = rest;
+    }
+
+    while let Some(start) = remaining.find(['%', '$']) {
+        result.push_str(&remaining[..start]);
+        let reference = &remaining[start..];
+
+        let (name, length) = match reference.as_bytes() {
+            [b'%', ..] => match reference[1..].find('%') {
+                Some(end) => (&reference[1..end + 1], end + 2),
+                None => ("", 1),
+            },
+            [b'$', b'{', ..] => match reference.find('}') {
+                Some(end) => (&reference[2..end], end + 1),
+                None => ("", 1),
+            },
+            _ => {
+                let end = reference[1..]
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .map_or(reference.len(), |end| end + 1);
+                (&reference[1..end], end)
+            }
+        };
+
+        match lookup(name).filter(|_| is_variable_name(name)) {
+            Some(value) => result.push_str(&value),
+            None => result.push_str(&reference[..length]),
+        }
+        remaining = &reference[length..];
+    }
+
+    result.push_str(remaining);
+    result
+}
+
+fn is_variable_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(source: &str) -> String {
+        expand_source_with(source, Some("C:\\Users\\user"), |name| match name {
+            "DATA" => Some("D:\\Data".to_owned()),
+            "USERNAME" => Some("user".to_owned()),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn when_no_references_then_unchanged() {
+        assert_eq!(
+            expand("C:\\Users\\user\\Documents"),
+            "C:\\Users\\user\\Documents"
+        );
+    }
+
+    #[test]
+    fn when_home_then_expanded() {
+        assert_eq!(expand("~\\Documents"), "C:\\Users\\user\\Documents");
+        assert_eq!(expand("~"), "C:\\Users\\user");
+        assert_eq!(expand("~user\\Documents"), "~user\\Documents");
+        assert_eq!(expand("C:\\~\\Documents"), "C:\\~\\Documents");
+    }
+
+    #[test]
+    fn when_percent_variables_then_expanded() {
+        assert_eq!(expand("%DATA%\\Photos"), "D:\\Data\\Photos");
+        assert_eq!(expand("C:\\Users\\%USERNAME%"), "C:\\Users\\user");
+    }
+
+    #[test]
+    fn when_dollar_variables_then_expanded() {
+        assert_eq!(expand("$DATA\\Photos"), "D:\\Data\\Photos");
+        assert_eq!(expand("${DATA}Photos"), "D:\\DataPhotos");
+    }
+
+    #[test]
+    fn when_variables_undefined_then_kept() {
+        assert_eq!(expand("C:\\$Recycle.Bin"), "C:\\$Recycle.Bin");
+        assert_eq!(expand("\\\\server\\c$\\share"), "\\\\server\\c$\\share");
+        assert_eq!(expand("%MISSING%\\%DATA%"), "%MISSING%\\D:\\Data");
+        assert_eq!(expand("100%"), "100%");
+        assert_eq!(expand("${DATA"), "${DATA");
+    }
+
+    #[test]
+    fn glob_patterns_detected() {
+        assert!(is_glob_pattern("C:\\Users\\*\\Documents"));
+        assert!(is_glob_pattern("D:\\file?.txt"));
+        assert!(!is_glob_pattern("D:\\Data"));
+    }
+
+    #[test]
+    fn when_literal_path_with_brackets_exists_then_matched_as_is() {
+        let root = env::temp_dir().join(format!("restic-service-sources-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("Photos [2020]")).unwrap();
+        std::fs::create_dir_all(root.join("Photos 2")).unwrap();
+        let literal = root.join("Photos [2020]").to_str().unwrap().to_owned();
+        let pattern = root.join("Photos [0-9]").to_str().unwrap().to_owned();
+
+        let literal_matches = match_source(&literal).unwrap();
+        let pattern_matches = match_source(&pattern).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(literal_matches, vec![literal]);
+        assert_eq!(
+            pattern_matches,
+            vec![root.join("Photos 2").to_str().unwrap().to_owned()]
+        );
+    }
+}
@@ -1,10 +1,14 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ServiceConfiguration {
     pub version: u32,
 
+    /// Glob patterns of additional files with jobs, relative to this file.
+    #[serde(default)]
+    pub include: Vec<String>,
+
     #[serde(default)]
     pub jobs: HashMap<String, ResticJob>,
 
@@ -12,15 +16,29 @@ pub struct ServiceConfiguration {
     pub api: ApiConfiguration,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(default)]
 pub struct ApiConfiguration {
     pub enabled: bool,
     pub host: String,
     pub port: u16,
     pub workers: usize,
+    /// Origins of web pages allowed to call the API, requests from other pages are rejected.
+    pub allowed_origins: Vec<String>,
 }
 
+/// The origins of the tray UI, its web view is the only page calling the API by default.
+#[cfg(not(debug_assertions))]
+pub const DEFAULT_API_ORIGINS: &[&str] = &["http://tauri.localhost", "tauri://localhost"];
+
+/// Debug builds also allow the dev server of the tray UI, as started by `tauri dev`.
+#[cfg(debug_assertions)]
+pub const DEFAULT_API_ORIGINS: &[&str] = &[
+    "http://tauri.localhost",
+    "tauri://localhost",
+    "http://localhost:1420",
+];
+
 impl Default for ApiConfiguration {
     fn default() -> Self {
         Self {
@@ -28,16 +46,30 @@ impl Default for ApiConfiguration {
             host: "localhost".to_owned(),
             port: 42038,
             workers: 2,
+            allowed_origins: DEFAULT_API_ORIGINS.iter().map(|x| x.to_string()).collect(),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ResticJob {
+    /// The template this job is based on, already merged in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+
     /// Required
     pub cron: String,
     pub repository: String,
-    pub password: String,
+
+    // Exactly one password source is required.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_file: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_command: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_env: Option<String>,
 
     // Optional
     #[serde(default)]
@@ -51,9 +83,87 @@ pub struct ResticJob {
 
     #[serde(default)]
     pub forget_and_purge: ForgetConfiguration,
+
+    #[serde(default)]
+    pub prune: PruneJobConfiguration,
+
+    #[serde(default)]
+    pub stats: StatsJobConfiguration,
+}
+
+impl ResticJob {
+    /// The configured password source, `None` unless exactly one source is set.
+    pub fn password_source(&self) -> Option<PasswordSource> {
+        let mut sources: Vec<_> = [
+            self.password.clone().map(PasswordSource::Password),
+            self.password_file.clone().map(PasswordSource::File),
+            self.password_command.clone().map(PasswordSource::Command),
+            self.password_env.clone().map(PasswordSource::Env),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        match sources.len() {
+            1 => sources.pop(),
+            _ => None,
+        }
+    }
+
+    /// A copy that is safe to display, the password, password command and environment values are masked.
+    pub fn masked(&self) -> ResticJob {
+        let mask = |_: &String| MASKED_VALUE.to_owned();
+        ResticJob {
+            password: self.password.as_ref().map(mask),
+            password_command: self.password_command.as_ref().map(mask),
+            environment: self
+                .environment
+                .keys()
+                .map(|key| (key.clone(), MASKED_VALUE.to_owned()))
+                .collect(),
+            ..self.clone()
+        }
+    }
+}
+
+/// Replaces secrets in [ResticJob::masked].
+pub const MASKED_VALUE: &str = "********";
+
+/// Where restic gets the repository password from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PasswordSource {
+    /// The password in plaintext.
+    Password(String),
+    /// A file containing the password, passed as `RESTIC_PASSWORD_FILE`.
+    File(String),
+    /// A command printing the password, passed as `RESTIC_PASSWORD_COMMAND`.
+    Command(String),
+    /// The name of an environment variable of the service holding the password.
+    Env(String),
+}
+
+impl PasswordSource {
+    pub fn kind(&self) -> PasswordSourceKind {
+        match self {
+            PasswordSource::Password(_) => PasswordSourceKind::Password,
+            PasswordSource::File(_) => PasswordSourceKind::PasswordFile,
+            PasswordSource::Command(_) => PasswordSourceKind::PasswordCommand,
+            PasswordSource::Env(_) => PasswordSourceKind::PasswordEnv,
+        }
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// The kind of [PasswordSource], safe to expose without leaking the secret.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PasswordSourceKind {
+    Password,
+    PasswordFile,
+    PasswordCommand,
+    PasswordEnv,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(default)]
 pub struct ClearLocksJobConfiguration {
     pub enabled: bool,
@@ -65,18 +175,128 @@ impl Default for ClearLocksJobConfiguration {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A prune on its own schedule, separate from the backup and forget of the job.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct PruneJobConfiguration {
+    pub enabled: bool,
+    /// When to prune, required when enabled.
+    pub cron: Option<String>,
+    pub dry_run: bool,
+    pub max_unused: Option<String>,
+    pub max_repack_size: Option<String>,
+    pub repack_cacheable_only: bool,
+    pub repack_small: bool,
+    pub repack_uncompressed: bool,
+    pub repack_smaller_than: Option<String>,
+    pub additional_flags: Vec<String>,
+}
+
+/// Statistics of the repository taken on their own schedule, kept to follow its growth.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatsJobConfiguration {
+    pub enabled: bool,
+    /// When to take the statistics, required when enabled.
+    pub cron: Option<String>,
+    /// What to count, each mode is a separate `restic stats`.
+    pub modes: Vec<StatsMode>,
+    /// Statistics are taken for each host, for the whole repository when empty.
+    pub hosts: Vec<String>,
+}
+
+impl Default for StatsJobConfiguration {
+    fn default() -> Self {
+        StatsJobConfiguration {
+            enabled: false,
+            cron: None,
+            modes: vec![StatsMode::RestoreSize, StatsMode::RawData],
+            hosts: Vec::new(),
+        }
+    }
+}
+
+/// The modes of `restic stats`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StatsMode {
+    RestoreSize,
+    FilesByContents,
+    RawData,
+    BlobsPerFile,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(default)]
 pub struct BackupJobConfiguration {
     pub use_fs_snapshot: bool,
     pub verbose: bool,
     pub exclude_caches: bool,
     pub source_fixed_drives: bool,
-    pub sources: Vec<String>,
+    /// Which drives `source_fixed_drives` selects.
+    pub fixed_drives: FixedDrivesConfiguration,
+    pub sources: Vec<BackupSource>,
+    /// What to do when a source that is not required does not exist.
+    pub missing_sources: MissingSourcePolicy,
     pub cleanup_cache: bool,
     #[cfg(not(windows))]
     pub one_file_system: bool,
     pub additional_flags: Vec<String>,
+
+    /// Patterns to exclude, see https://restic.readthedocs.io/en/stable/040_backup.html#excluding-files.
+    pub exclude: Vec<String>,
+    /// Same as `exclude`, but ignoring the casing of filenames.
+    pub iexclude: Vec<String>,
+    /// Files with exclude patterns, one per line.
+    pub exclude_file: Vec<String>,
+    /// Same as `exclude_file`, but ignoring the casing of filenames.
+    pub iexclude_file: Vec<String>,
+    /// Excludes directories containing a file with one of these names.
+    pub exclude_if_present: Vec<String>,
+    /// Excludes files larger than this size (e.g. "500M").
+    pub exclude_larger_than: Option<String>,
+    /// Files listing the files to backup, one pattern per line.
+    pub files_from: Vec<String>,
+    /// Files listing the files to backup, one filename per line.
+    pub files_from_verbatim: Vec<String>,
+    /// Files listing the files to backup, as null-terminated filenames.
+    pub files_from_raw: Vec<String>,
+    /// Backs up the output of this command instead of sources, the program followed by its arguments.
+    pub stdin_command: Vec<String>,
+    /// Name of the file holding the output of `stdin_command` in the snapshot.
+    pub stdin_filename: Option<String>,
+
+    /// Tags added to every snapshot, in addition to the job tag from [get_job_tag].
+    pub tags: Vec<String>,
+    /// Overrides the hostname stored in snapshots.
+    pub host: Option<String>,
+    /// Overrides the time stored in snapshots, in the format "2006-01-02 15:04:05".
+    pub time: Option<String>,
+    /// How the parent snapshot is selected, any of "host", "paths" and "tags" separated by comma.
+    pub group_by: Option<String>,
+
+    /// Snapshot used as parent, instead of the latest snapshot of the same group.
+    pub parent: Option<String>,
+    /// Re-reads all files, instead of skipping files unchanged since the parent snapshot.
+    pub force: bool,
+    /// Skips creating a snapshot when nothing changed, the run is recorded as unchanged.
+    pub skip_if_unchanged: bool,
+    /// Skips scanning the sources to estimate progress.
+    pub no_scan: bool,
+    /// Number of files read concurrently, restic reads 2 by default.
+    pub read_concurrency: Option<u32>,
+    /// Stores the access time of files and directories.
+    pub with_atime: bool,
+    /// Ignores inode number and ctime changes when checking for modified files.
+    pub ignore_inode: bool,
+    /// Ignores ctime changes when checking for modified files.
+    pub ignore_ctime: bool,
+    /// Number of errors on single items (e.g. unreadable files) kept with each run, the others are only counted.
+    pub max_errors: usize,
+    /// Writes the new and modified items of each backup to a manifest file, kept as long as the run.
+    pub manifest: Option<ManifestFormat>,
+    /// Compares each new snapshot with its parent, the counts of added, removed and changed items are kept with the run.
+    pub diff_with_parent: bool,
 }
 
 impl Default for BackupJobConfiguration {
@@ -86,16 +306,125 @@ impl Default for BackupJobConfiguration {
             verbose: false,
             exclude_caches: false,
             source_fixed_drives: false,
+            fixed_drives: FixedDrivesConfiguration::default(),
             sources: Vec::default(),
+            missing_sources: MissingSourcePolicy::default(),
             cleanup_cache: false,
             #[cfg(not(windows))]
             one_file_system: false,
             additional_flags: Vec::default(),
+            exclude: Vec::default(),
+            iexclude: Vec::default(),
+            exclude_file: Vec::default(),
+            iexclude_file: Vec::default(),
+            exclude_if_present: Vec::default(),
+            exclude_larger_than: None,
+            files_from: Vec::default(),
+            files_from_verbatim: Vec::default(),
+            files_from_raw: Vec::default(),
+            stdin_command: Vec::default(),
+            stdin_filename: None,
+            tags: Vec::default(),
+            host: None,
+            time: None,
+            group_by: None,
+            parent: None,
+            force: false,
+            skip_if_unchanged: false,
+            no_scan: false,
+            read_concurrency: None,
+            with_atime: false,
+            ignore_inode: false,
+            ignore_ctime: false,
+            max_errors: 100,
+            manifest: None,
+            diff_with_parent: false,
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+/// Filters for the drives found by `source_fixed_drives`, a drive must pass every filter to be selected.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct FixedDrivesConfiguration {
+    /// Only drives with one of these file systems (e.g. "NTFS"), ignoring case, any when empty.
+    pub file_systems: Vec<String>,
+    /// Only drives whose mount point matches one of these glob patterns, any when empty.
+    pub mount_points: Vec<String>,
+    /// Only drives with at least this much total space (e.g. "10G").
+    pub min_size: Option<String>,
+    /// Drives whose mount point matches one of these glob patterns are never selected.
+    pub exclude: Vec<String>,
+}
+
+/// A path to backup, either a plain string or a table with options.
+///
+/// Paths may contain glob patterns, a leading `~` and `%NAME%`, `$NAME` or `${NAME}` environment variables.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BackupSource {
+    Path(String),
+    Detailed {
+        path: String,
+        /// Fails the backup when the path does not exist (or a pattern matches nothing).
+        #[serde(default)]
+        required: bool,
+    },
+}
+
+impl BackupSource {
+    pub fn path(&self) -> &str {
+        match self {
+            BackupSource::Path(path) => path,
+            BackupSource::Detailed { path, .. } => path,
+        }
+    }
+
+    pub fn is_required(&self) -> bool {
+        match self {
+            BackupSource::Path(_) => false,
+            BackupSource::Detailed { required, .. } => *required,
+        }
+    }
+}
+
+/// What to do when a source does not exist, or a pattern matches nothing.
+///
+/// Required sources always fail the backup, and a backup without any source always fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MissingSourcePolicy {
+    /// Log a warning and backup the remaining sources.
+    #[default]
+    Warn,
+    /// Fail the backup.
+    Fail,
+    /// Backup the remaining sources without a warning.
+    Ignore,
+}
+
+/// How the manifest of new and modified items of a backup is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ManifestFormat {
+    /// JSON lines.
+    Plain,
+    /// JSON lines, compressed with gzip.
+    Gzip,
+}
+
+/// Prefix of the tag every snapshot made by a job is tagged with.
+pub const JOB_TAG_PREFIX: &str = "job:";
+
+/// The tag of pinned snapshots, unless `pin_tag` is configured.
+pub const DEFAULT_PIN_TAG: &str = "pinned";
+
+/// The tag added to every snapshot of a job, so forget policies and restores can select them.
+pub fn get_job_tag(job_id: &str) -> String {
+    format!("{JOB_TAG_PREFIX}{job_id}")
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(default)]
 pub struct ForgetConfiguration {
     pub enabled: bool,
@@ -109,17 +438,20 @@ pub struct ForgetConfiguration {
     pub keep_weekly: Option<u32>,
     pub keep_monthly: Option<u32>,
     pub keep_yearly: Option<u32>,
+    /// Durations like "1y5m7d2h".
     pub keep_within: Option<String>,
-    pub keep_within_hourly: Option<u32>,
-    pub keep_within_daily: Option<u32>,
-    pub keep_within_weekly: Option<u32>,
-    pub keep_within_monthly: Option<u32>,
-    pub keep_within_yearly: Option<u32>,
+    pub keep_within_hourly: Option<String>,
+    pub keep_within_daily: Option<String>,
+    pub keep_within_weekly: Option<String>,
+    pub keep_within_monthly: Option<String>,
+    pub keep_within_yearly: Option<String>,
     pub keep_tag: Option<String>,
+    /// The tag pinned snapshots get, they are always kept in addition to the policy. Empty disables pinning.
+    pub pin_tag: String,
 
     // Filtering options
     pub host: Option<String>,
-    pub tag: Option<String>,
+    pub tag: Option<TagFilter>,
     pub path: Option<String>,
 
     // Behavior options
@@ -136,3 +468,63 @@ pub struct ForgetConfiguration {
     pub repack_uncompressed: bool,
     pub repack_smaller_than: Option<String>,
 }
+
+impl ForgetConfiguration {
+    /// The tags forget selects snapshots by, a snapshot with any of them is considered.
+    ///
+    /// Unless `tag` is set, these are the job tag, so jobs sharing a repository only forget their own snapshots, and
+    /// no tags (an empty tag), for the snapshots made before job tags were added.
+    pub fn get_tag_filters(&self, job_id: &str) -> Vec<String> {
+        match &self.tag {
+            Some(TagFilter::Tag(tag)) => vec![tag.clone()],
+            Some(TagFilter::Tags(tags)) => tags.clone(),
+            None => vec![get_job_tag(job_id), String::new()],
+        }
+    }
+}
+
+/// The `tag` filter of forget, either a single tag or a list of tags.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TagFilter {
+    Tag(String),
+    /// Snapshots with any of the tags are considered, an empty list considers every snapshot.
+    Tags(Vec<String>),
+}
+
+impl Default for ForgetConfiguration {
+    fn default() -> Self {
+        ForgetConfiguration {
+            enabled: false,
+            additional_flags: Vec::default(),
+            group_by: None,
+            keep_last: None,
+            keep_hourly: None,
+            keep_daily: None,
+            keep_weekly: None,
+            keep_monthly: None,
+            keep_yearly: None,
+            keep_within: None,
+            keep_within_hourly: None,
+            keep_within_daily: None,
+            keep_within_weekly: None,
+            keep_within_monthly: None,
+            keep_within_yearly: None,
+            keep_tag: None,
+            pin_tag: DEFAULT_PIN_TAG.to_owned(),
+            host: None,
+            tag: None,
+            path: None,
+            unsafe_allow_remove_all: false,
+            compact: false,
+            dry_run: false,
+            prune: false,
+            max_unused: None,
+            max_repack_size: None,
+            repack_cacheable_only: false,
+            repack_small: false,
+            repack_uncompressed: false,
+            repack_smaller_than: None,
+        }
+    }
+}
@@ -0,0 +1,387 @@
+use crate::config::ConfigurationError;
+use toml::{Table, Value};
+
+const DEFAULTS_KEY: &str = "defaults";
+const TEMPLATES_KEY: &str = "templates";
+const EXTENDS_KEY: &str = "extends";
+
+const REPLACE_KEY: &str = "replace";
+
+/// Keys of a job where setting one clears the others, as only one of them may be set.
+const EXCLUSIVE_KEYS: &[&[&str]] = &[&[
+    "password",
+    "password_file",
+    "password_command",
+    "password_env",
+]];
+
+/// Applies the `[defaults]` table and the `[templates.*]` referenced by `extends` to every job.
+///
+/// A job starts from the defaults, then the template chain is merged in (the furthest ancestor first), then the
+/// job itself. See [merge_tables] for how values are merged.
+pub fn resolve_templates(document: &mut Table) -> Result<(), ConfigurationError> {
+    let defaults = match document.remove(DEFAULTS_KEY) {
+        Some(Value::Table(defaults)) => defaults,
+        Some(_) => {
+            return Err(ConfigurationError::InvalidConfiguration(format!(
+                "'{DEFAULTS_KEY}' must be a table"
+            )));
+        }
+        None => Table::new(),
+    };
+
+    let templates = match document.remove(TEMPLATES_KEY) {
+        Some(Value::Table(templates)) => templates,
+        Some(_) => {
+            return Err(ConfigurationError::InvalidConfiguration(format!(
+                "'{TEMPLATES_KEY}' must be a table"
+            )));
+        }
+        None => Table::new(),
+    };
+
+    let Some(Value::Table(jobs)) = document.get_mut("jobs") else {
+        return Ok(());
+    };
+
+    for (job_id, job) in jobs.iter_mut() {
+        let Value::Table(table) = job else {
+            return Err(ConfigurationError::InvalidConfiguration(format!(
+                "job '{job_id}' must be a table"
+            )));
+        };
+
+        let mut layers = vec![defaults.clone()];
+        if let Some(extends) = get_extends(table, &format!("job '{job_id}'"))? {
+            collect_template_chain(&templates, extends, &mut Vec::new(), &mut layers)?;
+        }
+        layers.push(table.clone());
+
+        let mut resolved = Table::new();
+        for layer in layers {
+            merge_job_tables(&mut resolved, layer);
+        }
+        *table = resolved;
+    }
+
+    Ok(())
+}
+
+/// Merges the options of a job, like [merge_tables], where setting one of the [EXCLUSIVE_KEYS] (e.g. `password_file`)
+/// replaces the others of its group (e.g. a `password_env` of the defaults).
+pub fn merge_job_tables(base: &mut Table, overlay: Table) {
+    for group in EXCLUSIVE_KEYS {
+        if group.iter().any(|key| overlay.contains_key(*key)) {
+            for key in *group {
+                base.remove(*key);
+            }
+        }
+    }
+    merge_tables(base, overlay);
+}
+
+/// Merges `overlay` into `base`, where `overlay` wins.
+///
+/// - Tables are merged key by key, recursively.
+/// - Arrays are appended to, skipping values the base array already contains.
+/// - An array written as `{ replace = [...] }` replaces the base array instead.
+/// - Anything else is replaced.
+pub fn merge_tables(base: &mut Table, overlay: Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), into_replacement(value)) {
+            (_, Ok(replacement)) => {
+                base.insert(key, replacement);
+            }
+            (Some(Value::Table(base)), Err(Value::Table(overlay))) => merge_tables(base, overlay),
+            (Some(Value::Array(base)), Err(Value::Array(overlay))) => {
+                for item in overlay {
+                    if !base.contains(&item) {
+                        base.push(item);
+                    }
+                }
+            }
+            (_, Err(value)) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// The array of a `{ replace = [...] }` table, or the value as is.
+fn into_replacement(value: Value) -> Result<Value, Value> {
+    match value {
+        Value::Table(mut table)
+            if table.len() == 1 && matches!(table.get(REPLACE_KEY), Some(Value::Array(_))) =>
+        {
+            Ok(table.remove(REPLACE_KEY).unwrap())
+        }
+        value => Err(value),
+    }
+}
+
+/// Adds the template and its own `extends` chain to the layers (the furthest ancestor first), without the
+/// `extends` keys.
+fn collect_template_chain<'a>(
+    templates: &'a Table,
+    name: &'a str,
+    chain: &mut Vec<&'a str>,
+    layers: &mut Vec<Table>,
+) -> Result<(), ConfigurationError> {
+    if chain.contains(&name) {
+        chain.push(name);
+        return Err(ConfigurationError::InvalidConfiguration(format!(
+            "templates extend each other in a cycle: {}",
+            chain.join(" -> ")
+        )));
+    }
+    chain.push(name);
+
+    let template = match templates.get(name) {
+        Some(Value::Table(template)) => template,
+        Some(_) => {
+            return Err(ConfigurationError::InvalidConfiguration(format!(
+                "template '{name}' must be a table"
+            )));
+        }
+        None => {
+            return Err(ConfigurationError::InvalidConfiguration(format!(
+                "template '{name}' is not defined"
+            )));
+        }
+    };
+
+    if let Some(parent) = get_extends(template, &format!("template '{name}'"))? {
+        collect_template_chain(templates, parent, chain, layers)?;
+    }
+
+    let mut template = template.clone();
+    template.remove(EXTENDS_KEY);
+    layers.push(template);
+    Ok(())
+}
+
+fn get_extends<'a>(table: &'a Table, owner: &str) -> Result<Option<&'a str>, ConfigurationError> {
+    match table.get(EXTENDS_KEY) {
+        Some(Value::String(extends)) => Ok(Some(extends)),
+        Some(_) => Err(ConfigurationError::InvalidConfiguration(format!(
+            "'{EXTENDS_KEY}' of {owner} must be the name of a template"
+        ))),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PasswordSource;
+    use crate::config::parser::parse_configuration;
+
+    #[test]
+    fn when_defaults_then_applied_to_every_job() {
+        let config = parse_configuration(
+            r#"
+            version = 1
+
+            [defaults]
+            repository = "C:\\default"
+            password = "secret"
+
+            [defaults.environment]
+            KEY = "default"
+
+            [jobs.a]
+            cron = "0 0 * * *"
+
+            [jobs.b]
+            cron = "0 0 * * *"
+            repository = "C:\\b"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.jobs["a"].repository, "C:\\default");
+        assert_eq!(config.jobs["a"].environment["KEY"], "default");
+        assert_eq!(config.jobs["b"].repository, "C:\\b");
+        assert_eq!(config.jobs["b"].password.as_deref(), Some("secret"));
+    }
+
+    #[test]
+    fn when_extends_then_template_chain_merged_in_order() {
+        let config = parse_configuration(
+            r#"
+            version = 1
+
+            [defaults]
+            password = "secret"
+
+            [defaults.forget_and_purge]
+            keep_daily = 7
+
+            [templates.base]
+            repository = "C:\\base"
+            forget_and_purge = { enabled = true, keep_daily = 14, keep_weekly = 4 }
+
+            [templates.long]
+            extends = "base"
+            forget_and_purge = { keep_weekly = 52 }
+
+            [jobs.a]
+            extends = "long"
+            cron = "0 0 * * *"
+            "#,
+        )
+        .unwrap();
+
+        let job = &config.jobs["a"];
+        assert_eq!(job.extends.as_deref(), Some("long"));
+        assert_eq!(job.repository, "C:\\base");
+        assert!(job.forget_and_purge.enabled);
+        assert_eq!(job.forget_and_purge.keep_daily, Some(14));
+        assert_eq!(job.forget_and_purge.keep_weekly, Some(52));
+    }
+
+    #[test]
+    fn when_arrays_merged_then_appended_without_duplicates() {
+        let config = parse_configuration(
+            r#"
+            version = 1
+
+            [defaults.backup]
+            additional_flags = ["--one-file-system", "--exclude=*.tmp"]
+
+            [jobs.a]
+            cron = "0 0 * * *"
+            repository = "C:\\a"
+            password = "secret"
+            backup = { additional_flags = ["--exclude=*.tmp", "--exclude=*.bak"] }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.jobs["a"].backup.additional_flags,
+            vec!["--one-file-system", "--exclude=*.tmp", "--exclude=*.bak"]
+        );
+    }
+
+    #[test]
+    fn when_array_replaced_then_base_values_dropped() {
+        let config = parse_configuration(
+            r#"
+            version = 1
+
+            [defaults.backup]
+            additional_flags = ["--one-file-system", "--exclude=*.tmp"]
+
+            [templates.full]
+            backup = { additional_flags = { replace = ["--exclude=*.bak"] } }
+
+            [jobs.a]
+            extends = "full"
+            cron = "0 0 * * *"
+            repository = "C:\\a"
+            password = "secret"
+
+            [jobs.b]
+            cron = "0 0 * * *"
+            repository = "C:\\b"
+            password = "secret"
+            backup = { additional_flags = { replace = [] } }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.jobs["a"].backup.additional_flags,
+            vec!["--exclude=*.bak"]
+        );
+        assert!(config.jobs["b"].backup.additional_flags.is_empty());
+    }
+
+    #[test]
+    fn when_job_sets_other_password_source_then_defaults_source_replaced() {
+        let config = parse_configuration(
+            r#"
+            version = 1
+
+            [defaults]
+            repository = "C:\\default"
+            password_env = "RESTIC_SECRET"
+
+            [templates.file]
+            password_command = "get-secret.exe"
+
+            [jobs.a]
+            cron = "0 0 * * *"
+            password_file = "C:\\secret.txt"
+
+            [jobs.b]
+            extends = "file"
+            cron = "0 0 * * *"
+
+            [jobs.c]
+            cron = "0 0 * * *"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.jobs["a"].password_source(),
+            Some(PasswordSource::File("C:\\secret.txt".to_owned()))
+        );
+        assert_eq!(
+            config.jobs["b"].password_source(),
+            Some(PasswordSource::Command("get-secret.exe".to_owned()))
+        );
+        assert_eq!(
+            config.jobs["c"].password_source(),
+            Some(PasswordSource::Env("RESTIC_SECRET".to_owned()))
+        );
+    }
+
+    #[test]
+    fn when_template_missing_then_invalid() {
+        let result = parse_configuration(
+            r#"
+            version = 1
+
+            [jobs.a]
+            extends = "missing"
+            cron = "0 0 * * *"
+            repository = "C:\\a"
+            password = "secret"
+            "#,
+        );
+
+        assert!(matches!(
+            result,
+            Err(ConfigurationError::InvalidConfiguration(_))
+        ));
+    }
+
+    #[test]
+    fn when_templates_cycle_then_invalid() {
+        let result = parse_configuration(
+            r#"
+            version = 1
+
+            [templates.a]
+            extends = "b"
+
+            [templates.b]
+            extends = "a"
+
+            [jobs.a]
+            extends = "a"
+            cron = "0 0 * * *"
+            repository = "C:\\a"
+            password = "secret"
+            "#,
+        );
+
+        assert!(matches!(
+            result,
+            Err(ConfigurationError::InvalidConfiguration(message)) if message.contains("a -> b -> a")
+        ));
+    }
+}
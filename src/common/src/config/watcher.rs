@@ -1,30 +1,47 @@
-use crate::config::parser::parse_configuration;
-use crate::config::{ConfigurationError, ServiceConfiguration};
-use log::{info, warn};
+use crate::config::{
+    ConfigurationError, ConfigurationSources, ServiceConfiguration, load_configuration,
+};
+use log::{debug, info, warn};
 use notify_debouncer_full::notify::{RecommendedWatcher, RecursiveMode};
 use notify_debouncer_full::{DebounceEventResult, Debouncer, RecommendedCache, new_debouncer};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use tokio::fs::read_to_string;
 use tokio_util::sync::CancellationToken;
 
 pub struct ConfigurationWithWatcher {
     // pub config: ServiceConfiguration,
     path: String,
-    debouncer: Option<Debouncer<RecommendedWatcher, RecommendedCache>>,
+    debouncer: Mutex<Option<Debouncer<RecommendedWatcher, RecommendedCache>>>,
+    /// Included files and directories currently watched, in addition to the main file.
+    watched: Mutex<HashMap<PathBuf, RecursiveMode>>,
+    /// The sources of the last read, changes to other files in the watched directories are ignored.
+    sources: Arc<Mutex<ConfigurationSources>>,
     tokens: Arc<Mutex<Vec<CancellationToken>>>,
 }
 
 impl ConfigurationWithWatcher {
     pub fn new(path: String) -> Self {
         let callbacks = Arc::new(Mutex::new(Vec::<CancellationToken>::new()));
+        let sources = Arc::new(Mutex::new(ConfigurationSources::default()));
 
         let handler = {
             let path = path.clone();
             let tokens = callbacks.clone();
+            let sources = sources.clone();
             move |result: DebounceEventResult| match result {
-                Ok(_) => {
-                    info!("Configuration file '{path}' changed.");
+                Ok(events) => {
+                    let sources = sources.lock().unwrap();
+                    if !events
+                        .iter()
+                        .any(|event| is_relevant_change(&event.paths, Path::new(&path), &sources))
+                    {
+                        debug!("Ignored changes next to the configuration file '{path}'.");
+                        return;
+                    }
+
+                    info!("Configuration file '{path}' (or one of its includes) changed.");
                     let mut tokens = tokens.lock().unwrap();
                     for token in tokens.iter() {
                         token.cancel();
@@ -57,27 +74,170 @@ impl ConfigurationWithWatcher {
 
         ConfigurationWithWatcher {
             path,
-            debouncer,
+            debouncer: Mutex::new(debouncer),
+            watched: Mutex::new(HashMap::new()),
+            sources,
             tokens: callbacks,
         }
     }
 
+    /// Registers a token that will be cancelled on the next change to the configuration file.
     pub fn register_cancellation_token(&self, token: &CancellationToken) {
         let mut callbacks = self.tokens.lock().unwrap();
         callbacks.push(token.clone());
     }
 
+    /// Reads the main configuration file, merges all included files, then validates the result.
     pub async fn read_configuration(&self) -> Result<ServiceConfiguration, ConfigurationError> {
-        let toml = read_to_string(&self.path).await?;
-        let config = parse_configuration(&toml)?;
-        Ok(config)
+        // Watch before reading the includes, so fixing a broken include triggers a reload.
+        load_configuration(Path::new(&self.path), |sources| {
+            self.update_watched(sources)
+        })
+        .await
+    }
+
+    fn update_watched(&self, sources: &ConfigurationSources) {
+        let mut debouncer = self.debouncer.lock().unwrap();
+        let Some(debouncer) = debouncer.as_mut() else {
+            return;
+        };
+
+        let mut next: HashMap<PathBuf, RecursiveMode> = sources
+            .files
+            .iter()
+            .map(|file| (file.clone(), RecursiveMode::NonRecursive))
+            .collect();
+        for directory in &sources.directories {
+            let mode = match directory.recursive {
+                true => RecursiveMode::Recursive,
+                false => RecursiveMode::NonRecursive,
+            };
+            next.insert(directory.path.clone(), mode);
+        }
+
+        // Watch the parent of a missing conf.d, unless it is watched for includes anyway.
+        if let Some(directory) = &sources.missing_configuration_directory
+            && let Some(parent) = directory.parent()
+            && !next.contains_key(parent)
+        {
+            next.insert(parent.to_path_buf(), RecursiveMode::NonRecursive);
+        }
+        *self.sources.lock().unwrap() = sources.clone();
+
+        let mut watched = self.watched.lock().unwrap();
+        for (path, mode) in watched.iter() {
+            if next.get(path) != Some(mode)
+                && let Err(e) = debouncer.unwatch(path)
+            {
+                debug!("Failed to unwatch '{}'. Error: {e:?}", path.display());
+            }
+        }
+        for (path, mode) in next.iter() {
+            if watched.get(path) != Some(mode)
+                && let Err(e) = debouncer.watch(path, *mode)
+            {
+                warn!(
+                    "Failed to watch '{}', changes to it will not be picked up. Error: {e:?}",
+                    path.display()
+                );
+            }
+        }
+        *watched = next;
     }
 }
 
+/// Whether a change may affect the configuration. The watched directories also hold other files (e.g. the logs next
+/// to the executable), so only the main file and the included files count.
+fn is_relevant_change(paths: &[PathBuf], main_path: &Path, sources: &ConfigurationSources) -> bool {
+    paths
+        .iter()
+        .any(|path| path == main_path || sources.is_included(path))
+}
+
 impl Drop for ConfigurationWithWatcher {
     fn drop(&mut self) {
-        if let Some(debouncer) = self.debouncer.take() {
+        if let Some(debouncer) = self.debouncer.lock().unwrap().take() {
             debouncer.stop_nonblocking();
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::resolve_configuration_sources;
+
+    fn get_sources(root: &Path, include: &[&str]) -> ConfigurationSources {
+        let include: Vec<_> = include.iter().map(|x| x.to_string()).collect();
+        resolve_configuration_sources(&root.join("service_config.toml"), &include).unwrap()
+    }
+
+    #[test]
+    fn when_configuration_directory_is_created_then_relevant() {
+        let root = std::env::temp_dir().join(format!(
+            "restic-service-watch-conf-d-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&root).unwrap();
+        let sources = get_sources(&root, &[]);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let main_path = root.join("service_config.toml");
+        assert!(is_relevant_change(
+            &[root.join("conf.d")],
+            &main_path,
+            &sources
+        ));
+        assert!(is_relevant_change(
+            &[root.join("conf.d").join("a.toml")],
+            &main_path,
+            &sources
+        ));
+        assert!(is_relevant_change(
+            std::slice::from_ref(&main_path),
+            &main_path,
+            &sources
+        ));
+    }
+
+    #[test]
+    fn when_included_files_change_then_relevant() {
+        let root = std::env::temp_dir().join(format!(
+            "restic-service-watch-includes-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(root.join("jobs").join("nested")).unwrap();
+        let sources = get_sources(&root, &["./jobs/**/*.toml", "jobs-*.toml"]);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let main_path = root.join("service_config.toml");
+        for path in [
+            root.join("jobs").join("nested").join("a.toml"),
+            root.join("jobs").join("b.toml"),
+            root.join("jobs-new.toml"),
+        ] {
+            assert!(is_relevant_change(&[path], &main_path, &sources));
+        }
+    }
+
+    #[test]
+    fn when_other_files_in_watched_directory_change_then_ignored() {
+        let root = std::env::temp_dir().join(format!(
+            "restic-service-watch-others-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&root).unwrap();
+        let sources = get_sources(&root, &["jobs-*.toml"]);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let main_path = root.join("service_config.toml");
+        for path in [
+            root.join("service.log"),
+            root.join("run_history.json"),
+            root.join("manifests").join("a.jsonl"),
+            root.join("jobs-a.toml.bak"),
+        ] {
+            assert!(!is_relevant_change(&[path], &main_path, &sources));
+        }
+    }
+}
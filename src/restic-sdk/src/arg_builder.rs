@@ -3,6 +3,7 @@ use ordermap::OrderSet;
 #[derive(Debug, Clone, Default)]
 pub struct ArgumentsBuilder {
     arguments: OrderSet<Argument>,
+    trailing_values: Vec<String>,
 }
 
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Ord, PartialOrd)]
@@ -48,6 +49,16 @@ impl ArgumentsBuilder {
         self
     }
 
+    /// Values passed after `--` at the very end, kept as is (not deduplicated) and never parsed as flags.
+    pub fn with_trailing_values(
+        mut self,
+        values: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.trailing_values
+            .extend(values.into_iter().map(|value| value.into()));
+        self
+    }
+
     pub fn build(self) -> Vec<String> {
         let mut full_arguments = vec![];
 
@@ -69,6 +80,11 @@ impl ArgumentsBuilder {
             }
         }
 
+        if !self.trailing_values.is_empty() {
+            full_arguments.push("--".to_owned());
+            full_arguments.extend(self.trailing_values);
+        }
+
         full_arguments
     }
 
@@ -173,6 +189,19 @@ mod tests {
         assert_eq!(command, vec!["list", "locks", "--json"]);
     }
 
+    #[test]
+    fn when_trailing_values_then_last_after_separator_and_kept_as_is() {
+        let command = ArgumentsBuilder::new()
+            .with_verb("backup")
+            .with_trailing_values(["pg_dump", "-c", "-c"])
+            .with_flag("json")
+            .build();
+        assert_eq!(
+            command,
+            vec!["backup", "--json", "--", "pg_dump", "-c", "-c"]
+        );
+    }
+
     #[test]
     fn can_accept_str() {
         // This compiles.
@@ -1,7 +1,9 @@
 use crate::errors::ResticError;
-use crate::messages::{BackupSummary, ResticBackupMessage};
+use crate::exec::ResticInput;
+use crate::messages::{BackupError, BackupSummary, BackupVerboseStatus, ResticBackupMessage};
 use crate::{ArgumentsBuilder, BuilderValue, Restic};
 use log::{debug, warn};
+use tokio::io::AsyncRead;
 use tokio_util::sync::CancellationToken;
 
 impl Restic {
@@ -10,23 +12,117 @@ impl Restic {
         paths: impl IntoIterator<Item = impl Into<String>>,
         options: BackupOptions,
         cancellation_token: &CancellationToken,
+    ) -> Result<BackupResult, ResticError> {
+        self.backup_with_items(paths, options, |_| {}, cancellation_token)
+            .await
+    }
+
+    /// Same as `backup`, but calls `on_item` for every item restic reports, with its action (new, modified or
+    /// unchanged) and size.
+    ///
+    /// Restic only reports items with a verbosity of 2 or more, see `BackupOptions::with_verbose_level`.
+    pub async fn backup_with_items<F>(
+        &self,
+        paths: impl IntoIterator<Item = impl Into<String>>,
+        options: BackupOptions,
+        on_item: F,
+        cancellation_token: &CancellationToken,
+    ) -> Result<BackupResult, ResticError>
+    where
+        F: FnMut(BackupVerboseStatus),
+    {
+        let arguments = options.builder.with_values(paths);
+        self.run_backup(
+            arguments,
+            options.max_errors,
+            None,
+            false,
+            on_item,
+            cancellation_token,
+        )
+        .await
+    }
+
+    /// Backs up the data read from the input as a single file, see `BackupOptions::with_stdin_filename`.
+    pub async fn backup_stdin(
+        &self,
+        input: impl AsyncRead + Unpin + Send + 'static,
+        options: BackupOptions,
+        cancellation_token: &CancellationToken,
+    ) -> Result<BackupResult, ResticError> {
+        let arguments = options.builder.with_flag("stdin");
+        let input: ResticInput = Box::new(input);
+        self.run_backup(
+            arguments,
+            options.max_errors,
+            Some(input),
+            false,
+            |_| {},
+            cancellation_token,
+        )
+        .await
+    }
+
+    /// Backs up the output of the command as a single file, see `BackupOptions::with_stdin_filename`.
+    ///
+    /// The first value is the program, the others its arguments. When the command exits with a non-zero code (or can
+    /// not be started) restic fails without creating a snapshot, reported as `ResticError::StdinCommandFailed`.
+    /// Failures with their own exit code, like a wrong password, are reported as usual.
+    pub async fn backup_from_command(
+        &self,
+        command: impl IntoIterator<Item = impl Into<String>>,
+        options: BackupOptions,
+        cancellation_token: &CancellationToken,
     ) -> Result<BackupResult, ResticError> {
         let arguments = options
             .builder
-            .with_values(paths);
+            .with_flag("stdin-from-command")
+            .with_trailing_values(command);
+        self.run_backup(
+            arguments,
+            options.max_errors,
+            None,
+            true,
+            |_| {},
+            cancellation_token,
+        )
+        .await
+    }
 
+    async fn run_backup<F>(
+        &self,
+        arguments: ArgumentsBuilder,
+        max_errors: usize,
+        input: Option<ResticInput>,
+        from_command: bool,
+        mut on_item: F,
+        cancellation_token: &CancellationToken,
+    ) -> Result<BackupResult, ResticError>
+    where
+        F: FnMut(BackupVerboseStatus),
+    {
         let mut summary: Option<BackupSummary> = None;
+        let mut errors = Vec::new();
+        let mut error_count = 0;
+        let mut exit_message = None;
 
         let result = self
-            .exec_json(
+            .exec_json_with_input(
                 arguments,
+                input,
                 |message: ResticBackupMessage| match message {
                     ResticBackupMessage::BackupSummary(message) => summary = Some(message),
                     ResticBackupMessage::BackupStatus(_) => {
                         debug!("Backup status: {message:?}");
                     }
                     ResticBackupMessage::BackupError(error) => {
-                        debug!("Backup error: {error}")
+                        error_count += 1;
+                        if errors.len() < max_errors {
+                            warn!("Backup error: {error}");
+                            errors.push(error);
+                        } else {
+                            debug!("Backup error: {error}");
+                        }
                     }
                     ResticBackupMessage::ExitError(error) => {
                         warn!(
@@ -34,10 +130,9 @@ impl Restic {
                             error = error.message,
                             code = error.code
                         );
+                        exit_message = Some(error.message);
                     }
-                    ResticBackupMessage::BackupVerboseStatus(_) => {
-                        // Ignored.
-                    }
+                    ResticBackupMessage::BackupVerboseStatus(status) => on_item(status),
                 },
                 cancellation_token,
             )
@@ -47,13 +142,21 @@ impl Restic {
             (Ok(_), Some(summary)) => Ok(BackupResult {
                 failed_to_read_some_data: false,
                 summary,
+                errors,
+                error_count,
             }),
             (Err(ResticError::BackupFailedToReadSomeSourceData), Some(summary)) => {
                 Ok(BackupResult {
                     failed_to_read_some_data: true,
                     summary,
+                    errors,
+                    error_count,
                 })
             }
+            // Restic has no exit code of its own for a failed command, it is the generic one, before any summary.
+            (Err(ResticError::GenericError), None) if from_command => Err(
+                ResticError::StdinCommandFailed(exit_message.unwrap_or_default()),
+            ),
             (Err(e), _) => Err(e),
             (Ok(_), None) => Err(ResticError::UnexpectedResponse(
                 "Backup did not return a summary".to_string(),
@@ -69,17 +172,35 @@ pub struct BackupResult {
 
     /// The summary of the backup operation.
     pub summary: BackupSummary,
+
+    /// The errors restic reported for single items (e.g. unreadable files), at most `BackupOptions::with_max_errors`.
+    pub errors: Vec<BackupError>,
+
+    /// The number of errors reported, including the ones dropped over the maximum.
+    pub error_count: usize,
+}
+
+impl BackupResult {
+    /// Whether no snapshot was created because nothing changed, see `BackupOptions::with_skip_if_unchanged`.
+    pub fn is_unchanged(&self) -> bool {
+        self.summary.snapshot_id.is_none() && !self.summary.dry_run
+    }
 }
 
+/// Number of item errors kept in the `BackupResult` by default.
+pub const DEFAULT_MAX_BACKUP_ERRORS: usize = 1000;
+
 #[derive(Debug, Clone)]
 pub struct BackupOptions {
     builder: ArgumentsBuilder,
+    max_errors: usize,
 }
 
 impl Default for BackupOptions {
     fn default() -> Self {
         Self {
             builder: ArgumentsBuilder::new().with_verb("backup"),
+            max_errors: DEFAULT_MAX_BACKUP_ERRORS,
         }
     }
 }
@@ -106,6 +227,12 @@ impl BackupOptions {
         self.with_flag("verbose")
     }
 
+    /// Set the verbosity level, from 0 (quiet) to 3 (debug). Level 2 reports every backed up item.
+    pub fn with_verbose_level(self, level: u32) -> Self {
+        // The verbosity is a count flag, it only accepts a value in the "--flag=value" form.
+        self.with_flag(&format!("verbose={level}"))
+    }
+
     /// Auto remove old cache directories.
     pub fn with_cleanup_cache(self) -> Self {
         self.with_flag("cleanup-cache")
@@ -117,6 +244,124 @@ impl BackupOptions {
         self.with_flag("exclude-caches")
     }
 
+    /// Exclude a pattern, can be used multiple times.
+    pub fn with_exclude(self, pattern: &str) -> Self {
+        self.with_flag_and_value("exclude", pattern)
+    }
+
+    /// Same as `with_exclude`, but ignores the casing of filenames.
+    pub fn with_iexclude(self, pattern: &str) -> Self {
+        self.with_flag_and_value("iexclude", pattern)
+    }
+
+    /// Read exclude patterns from a file, can be used multiple times.
+    pub fn with_exclude_file(self, path: &str) -> Self {
+        self.with_flag_and_value("exclude-file", path)
+    }
+
+    /// Same as `with_exclude_file`, but ignores the casing of filenames.
+    pub fn with_iexclude_file(self, path: &str) -> Self {
+        self.with_flag_and_value("iexclude-file", path)
+    }
+
+    /// Exclude directories containing a file with this name, can be used multiple times.
+    /// The filename may be followed by a colon and the header the file must start with, e.g. "CACHEDIR.TAG:Signature".
+    pub fn with_exclude_if_present(self, filename: &str) -> Self {
+        self.with_flag_and_value("exclude-if-present", filename)
+    }
+
+    /// Exclude files larger than the given size (e.g. "500M").
+    pub fn with_exclude_larger_than(self, size: &str) -> Self {
+        self.with_flag_and_value("exclude-larger-than", size)
+    }
+
+    /// Read the files to backup from a file, one pattern per line, can be used multiple times.
+    pub fn with_files_from(self, path: &str) -> Self {
+        self.with_flag_and_value("files-from", path)
+    }
+
+    /// Read the files to backup from a file, one filename per line without pattern matching.
+    pub fn with_files_from_verbatim(self, path: &str) -> Self {
+        self.with_flag_and_value("files-from-verbatim", path)
+    }
+
+    /// Read the files to backup from a file, as null-terminated filenames.
+    pub fn with_files_from_raw(self, path: &str) -> Self {
+        self.with_flag_and_value("files-from-raw", path)
+    }
+
+    /// Add a tag to the new snapshot, can be used multiple times.
+    pub fn with_tag(self, tag: &str) -> Self {
+        self.with_flag_and_value("tag", tag)
+    }
+
+    /// Set the hostname of the snapshot, instead of the hostname of this machine.
+    pub fn with_host(self, host: &str) -> Self {
+        self.with_flag_and_value("host", host)
+    }
+
+    /// Set the time of the snapshot, in the format "2006-01-02 15:04:05".
+    pub fn with_time(self, time: &str) -> Self {
+        self.with_flag_and_value("time", time)
+    }
+
+    /// Group snapshots by host, paths and/or tags (separated by comma) when looking for the parent snapshot.
+    pub fn with_group_by(self, group_by: &str) -> Self {
+        self.with_flag_and_value("group-by", group_by)
+    }
+
+    /// Use this snapshot as the parent, instead of the latest snapshot of the same group.
+    pub fn with_parent(self, snapshot_id: &str) -> Self {
+        self.with_flag_and_value("parent", snapshot_id)
+    }
+
+    /// Force re-reading all files, instead of skipping the files unchanged since the parent snapshot.
+    pub fn with_force(self) -> Self {
+        self.with_flag("force")
+    }
+
+    /// Skip creating a snapshot when nothing changed since the parent snapshot.
+    /// The summary of such a backup has no snapshot id.
+    pub fn with_skip_if_unchanged(self) -> Self {
+        self.with_flag("skip-if-unchanged")
+    }
+
+    /// Do not scan the sources to estimate the progress, saving time and IO on large sources.
+    pub fn with_no_scan(self) -> Self {
+        self.with_flag("no-scan")
+    }
+
+    /// Read this many files concurrently (restic default is 2).
+    pub fn with_read_concurrency(self, concurrency: u32) -> Self {
+        self.with_flag_and_value("read-concurrency", concurrency)
+    }
+
+    /// Store the access time of files and directories.
+    pub fn with_atime(self) -> Self {
+        self.with_flag("with-atime")
+    }
+
+    /// Ignore inode number and ctime changes when checking for modified files.
+    pub fn with_ignore_inode(self) -> Self {
+        self.with_flag("ignore-inode")
+    }
+
+    /// Ignore ctime changes when checking for modified files.
+    pub fn with_ignore_ctime(self) -> Self {
+        self.with_flag("ignore-ctime")
+    }
+
+    /// Name of the file in the snapshot when backing up stdin or the output of a command, "stdin" by default.
+    pub fn with_stdin_filename(self, filename: &str) -> Self {
+        self.with_flag_and_value("stdin-filename", filename)
+    }
+
+    /// Keep at most this many item errors in the result, the others are only counted.
+    pub fn with_max_errors(mut self, max_errors: usize) -> Self {
+        self.max_errors = max_errors;
+        self
+    }
+
     pub fn with_flag(mut self, name: &str) -> Self {
         self.builder = self.builder.with_flag(name);
         self
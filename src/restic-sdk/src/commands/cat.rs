@@ -1,6 +1,7 @@
-use crate::commands::exec::MessageOutputType;
 use crate::errors::ResticError;
 use crate::{ArgumentsBuilder, Restic};
+use log::debug;
+use tokio::io::AsyncWrite;
 use tokio_util::sync::CancellationToken;
 
 impl Restic {
@@ -11,19 +12,35 @@ impl Restic {
         path: &str,
         cancellation_token: &CancellationToken,
     ) -> Result<String, ResticError> {
-        let mut output = String::new();
-        self.exec(
-            ArgumentsBuilder::new().with_verb("cat").with_value(path),
-            |message, output_type| {
-                if output_type == MessageOutputType::Stdout {
-                    output.push_str(&message)
-                }
-            },
+        let mut output = Vec::new();
+        self.cat_to(path, None, &mut output, cancellation_token)
+            .await?;
+
+        Ok(String::from_utf8_lossy(&output).into_owned())
+    }
+
+    /// Writes the raw content of an object of the repository to the output as restic prints it, e.g. `pack` and its
+    /// ID, or `config` without one. Binary contents are copied as is and never buffered.
+    pub async fn cat_to<W>(
+        &self,
+        object_type: &str,
+        id: Option<&str>,
+        output: W,
+        cancellation_token: &CancellationToken,
+    ) -> Result<(), ResticError>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        self.exec_with_output(
+            ArgumentsBuilder::new()
+                .with_verb("cat")
+                .with_value(object_type)
+                .with_values(id),
+            output,
+            |line| debug!("Restic stderr: {line}"),
             cancellation_token,
         )
-        .await?;
-
-        Ok(output)
+        .await
     }
 
     /// Checks if the Restic repository exists and can be opened using the configured password.
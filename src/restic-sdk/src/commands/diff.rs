@@ -0,0 +1,102 @@
+use crate::errors::ResticError;
+use crate::messages::{DiffChange, DiffStatistics, ResticDiffMessage};
+use crate::{ArgumentsBuilder, BuilderValue, Restic};
+use log::warn;
+use tokio_util::sync::CancellationToken;
+
+impl Restic {
+    /// Compares two snapshots (IDs or "latest"), passing each item that differs to `on_change` as restic prints it.
+    /// Returns the statistics of the differences.
+    pub async fn diff<F>(
+        &self,
+        first_snapshot_id: &str,
+        second_snapshot_id: &str,
+        options: DiffOptions,
+        mut on_change: F,
+        cancellation_token: &CancellationToken,
+    ) -> Result<DiffStatistics, ResticError>
+    where
+        F: FnMut(DiffChange),
+    {
+        let mut statistics = None;
+        self.exec_json(
+            options
+                .builder
+                .with_trailing_values([first_snapshot_id, second_snapshot_id]),
+            |message: ResticDiffMessage| match message {
+                ResticDiffMessage::DiffChange(change) => on_change(change),
+                ResticDiffMessage::DiffStatistics(message) => statistics = Some(message),
+                ResticDiffMessage::ExitError(error) => {
+                    warn!(
+                        "Restic will exit with: {error} (code: {code})",
+                        error = error.message,
+                        code = error.code
+                    );
+                }
+            },
+            cancellation_token,
+        )
+        .await?;
+
+        statistics
+            .ok_or_else(|| ResticError::UnexpectedResponse("diff printed no statistics".to_owned()))
+    }
+}
+
+/// Options for the `restic diff` command.
+#[derive(Debug, Clone)]
+pub struct DiffOptions {
+    builder: ArgumentsBuilder,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            builder: ArgumentsBuilder::new().with_verb("diff"),
+        }
+    }
+}
+
+impl DiffOptions {
+    /// Creates a default `DiffOptions` instance, comparing the contents of the snapshots.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the `--metadata` flag.
+    ///
+    /// Also report items whose metadata (e.g. modification time) changed but not their contents.
+    pub fn metadata(self) -> Self {
+        self.with_flag("metadata")
+    }
+
+    /// Adds a flag without a value.
+    pub fn with_flag(mut self, name: &str) -> Self {
+        self.builder = self.builder.with_flag(name);
+        self
+    }
+
+    /// Adds a flag with a value.
+    pub fn with_flag_and_value<V: BuilderValue>(mut self, name: &str, value: V) -> Self {
+        self.builder = self.builder.with_flag_and_value(name, value);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_build_arguments() {
+        let options = DiffOptions::new().metadata();
+
+        assert_eq!(
+            options
+                .builder
+                .with_trailing_values(["1234", "5678"])
+                .build(),
+            vec!["diff", "--metadata", "--", "1234", "5678"]
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use crate::errors::ResticError;
+use crate::{ArgumentsBuilder, BuilderValue, Restic};
+use log::debug;
+use std::fmt::{Display, Formatter};
+use tokio::io::AsyncWrite;
+use tokio_util::sync::CancellationToken;
+
+impl Restic {
+    /// Writes the contents of a file of the snapshot (an ID or "latest") to the output as restic prints them, or an
+    /// archive of a directory, see `DumpOptions::archive`.
+    ///
+    /// Nothing is buffered, so any size can be dumped. Restic stops when writing to the output fails.
+    pub async fn dump<W>(
+        &self,
+        snapshot_id: &str,
+        path: &str,
+        options: DumpOptions,
+        output: W,
+        cancellation_token: &CancellationToken,
+    ) -> Result<(), ResticError>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        self.exec_with_output(
+            options.builder.with_trailing_values([snapshot_id, path]),
+            output,
+            |line| debug!("Restic stderr: {line}"),
+            cancellation_token,
+        )
+        .await
+    }
+}
+
+/// The archive formats directories are dumped as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DumpArchive {
+    /// The default of restic.
+    #[default]
+    Tar,
+    Zip,
+}
+
+impl DumpArchive {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DumpArchive::Tar => "tar",
+            DumpArchive::Zip => "zip",
+        }
+    }
+}
+
+impl Display for DumpArchive {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl BuilderValue for DumpArchive {
+    fn to_builder_value(&self) -> String {
+        self.as_str().to_owned()
+    }
+}
+
+/// Options for the `restic dump` command.
+#[derive(Debug, Clone)]
+pub struct DumpOptions {
+    builder: ArgumentsBuilder,
+}
+
+impl Default for DumpOptions {
+    fn default() -> Self {
+        Self {
+            builder: ArgumentsBuilder::new().with_verb("dump"),
+        }
+    }
+}
+
+impl DumpOptions {
+    /// Creates a default `DumpOptions` instance, dumping directories as tar archives.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the `--archive` flag.
+    ///
+    /// The format directories are dumped as, single files are always dumped as is.
+    pub fn archive(self, archive: DumpArchive) -> Self {
+        self.with_flag_and_value("archive", archive)
+    }
+
+    /// Sets the `--host` flag.
+    ///
+    /// Only consider snapshots for this host, when the snapshot is "latest".
+    pub fn host(self, value: &str) -> Self {
+        self.with_flag_and_value("host", value)
+    }
+
+    /// Sets the `--tag` flag.
+    ///
+    /// Only consider snapshots including the given tags, when the snapshot is "latest".
+    pub fn tag(self, value: &str) -> Self {
+        self.with_flag_and_value("tag", value)
+    }
+
+    /// Sets the `--path` flag.
+    ///
+    /// Only consider snapshots including this (absolute) path, when the snapshot is "latest".
+    pub fn path(self, value: &str) -> Self {
+        self.with_flag_and_value("path", value)
+    }
+
+    /// Adds a flag without a value.
+    pub fn with_flag(mut self, name: &str) -> Self {
+        self.builder = self.builder.with_flag(name);
+        self
+    }
+
+    /// Adds a flag with a value.
+    pub fn with_flag_and_value<V: BuilderValue>(mut self, name: &str, value: V) -> Self {
+        self.builder = self.builder.with_flag_and_value(name, value);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_build_arguments() {
+        let options = DumpOptions::new().archive(DumpArchive::Zip).host("laptop");
+
+        assert_eq!(
+            options
+                .builder
+                .with_trailing_values(["latest", "/home/user"])
+                .build(),
+            vec![
+                "dump",
+                "--archive",
+                "zip",
+                "--host",
+                "laptop",
+                "--",
+                "latest",
+                "/home/user"
+            ]
+        );
+    }
+}
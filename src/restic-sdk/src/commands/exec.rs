@@ -10,60 +10,143 @@ use std::ffi::OsString;
 use std::fmt::Display;
 use std::io;
 use std::process::{ExitStatus, Stdio};
-use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::process::Command;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::process::{Child, Command};
 use tokio_util::sync::CancellationToken;
 
+/// Size of the chunks the output of restic is read in.
+const OUTPUT_BUFFER_SIZE: usize = 64 * 1024;
+
 impl Restic {
     /// Low-level command execution method that allows for custom handling of output messages.
     pub(crate) async fn exec<F>(
         &self,
         arguments: ArgumentsBuilder,
-        mut on_message: F,
+        on_message: F,
         cancellation_token: &CancellationToken,
     ) -> Result<(), ResticError>
     where
         F: FnMut(String, MessageOutputType),
     {
-        let start = async || -> Result<ExitStatus, io::Error> {
-            let binary_path = Self::get_binary_path()?;
-            let arguments = arguments.build();
-            info!("Executing restic command: '{binary_path:?} {arguments:?}'");
-            let mut process = Command::new(binary_path)
-                .args(arguments)
-                .stdin(Stdio::null())
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .envs(self.config.environment.clone())
-                .creation_flags(CREATE_NEW_PROCESS_GROUP | CREATE_NEW_CONSOLE)
-                .kill_on_drop(true)
-                .spawn()?;
-
-            let stdout = process.stdout.take().unwrap();
-            let stderr = process.stderr.take().unwrap();
-            let mut stdout_lines = BufReader::new(stdout).lines();
-            let mut stderr_lines = BufReader::new(stderr).lines();
+        self.exec_with_input(arguments, None, on_message, cancellation_token)
+            .await
+    }
+
+    /// Same as `exec`, but writes the input to the stdin of restic, closing it once the input ends.
+    pub(crate) async fn exec_with_input<F>(
+        &self,
+        arguments: ArgumentsBuilder,
+        input: Option<ResticInput>,
+        on_message: F,
+        cancellation_token: &CancellationToken,
+    ) -> Result<(), ResticError>
+    where
+        F: FnMut(String, MessageOutputType),
+    {
+        let output = LineOutput {
+            on_message,
+            stdout_lines: LineBuffer::default(),
+        };
+        self.exec_raw(arguments, input, output, cancellation_token)
+            .await
+    }
+
+    /// Same as `exec`, but copies the stdout of restic as is to the output, e.g. for binary contents. Stderr is still
+    /// passed to `on_stderr` line by line.
+    pub(crate) async fn exec_with_output<W, F>(
+        &self,
+        arguments: ArgumentsBuilder,
+        output: W,
+        on_stderr: F,
+        cancellation_token: &CancellationToken,
+    ) -> Result<(), ResticError>
+    where
+        W: AsyncWrite + Unpin,
+        F: FnMut(String),
+    {
+        let output = WriterOutput { output, on_stderr };
+        self.exec_raw(arguments, None, output, cancellation_token)
+            .await
+    }
+
+    /// Same as `exec`, but passes the stdout of restic to `on_stdout` in chunks as read, e.g. for output that is not
+    /// split into lines. Stderr is still passed to `on_stderr` line by line.
+    pub(crate) async fn exec_with_chunks<F, E>(
+        &self,
+        arguments: ArgumentsBuilder,
+        on_stdout: F,
+        on_stderr: E,
+        cancellation_token: &CancellationToken,
+    ) -> Result<(), ResticError>
+    where
+        F: FnMut(&[u8]),
+        E: FnMut(String),
+    {
+        let output = ChunkOutput {
+            on_stdout,
+            on_stderr,
+        };
+        self.exec_raw(arguments, None, output, cancellation_token)
+            .await
+    }
+
+    /// The execution the others build on, stdout is passed to the output in chunks as read and stderr line by line.
+    async fn exec_raw<O>(
+        &self,
+        arguments: ArgumentsBuilder,
+        input: Option<ResticInput>,
+        mut output: O,
+        cancellation_token: &CancellationToken,
+    ) -> Result<(), ResticError>
+    where
+        O: ProcessOutput,
+    {
+        let start = async move || -> Result<ExitStatus, io::Error> {
+            let mut process = self.spawn(arguments, input.is_some())?;
+
+            let mut stdout = process.stdout.take().unwrap();
+            let mut stderr = process.stderr.take().unwrap();
+            let mut stdout_buffer = vec![0; OUTPUT_BUFFER_SIZE];
+            let mut stderr_buffer = vec![0; OUTPUT_BUFFER_SIZE];
+            let mut stderr_lines = LineBuffer::default();
             let mut stdout_complete = false;
             let mut stderr_complete = false;
 
+            let stdin = process.stdin.take();
+            let write_input = async move {
+                if let (Some(mut input), Some(mut stdin)) = (input, stdin) {
+                    tokio::io::copy(&mut input, &mut stdin).await?;
+                    stdin.shutdown().await?;
+                }
+                Ok::<(), io::Error>(())
+            };
+            tokio::pin!(write_input);
+            let mut input_complete = false;
+
             loop {
                 tokio::select! {
-                    stdout_line = stdout_lines.next_line(), if !stdout_complete => {
-                        match stdout_line? {
-                            None => {
-                                stdout_complete = true;
-                                continue;
-                            }
-                            Some(line) => on_message(line, MessageOutputType::Stdout),
+                    result = &mut write_input, if !input_complete => {
+                        input_complete = true;
+                        if let Err(e) = result {
+                            // Restic stops reading when it fails, the exit code tells why.
+                            debug!("Failed to write the input to restic: {e}");
                         }
                     },
-                    stderr_lines = stderr_lines.next_line(), if !stderr_complete => {
-                        match stderr_lines? {
-                            None => {
+                    read = stdout.read(&mut stdout_buffer), if !stdout_complete => {
+                        match read? {
+                            0 => stdout_complete = true,
+                            read => output.write_stdout(&stdout_buffer[..read]).await?,
+                        }
+                    },
+                    read = stderr.read(&mut stderr_buffer), if !stderr_complete => {
+                        match read? {
+                            0 => {
                                 stderr_complete = true;
-                                continue;
+                                stderr_lines.finish(|line| output.write_stderr_line(line));
                             }
-                            Some(line) => on_message(line, MessageOutputType::Stderr),
+                            read => stderr_lines.push(&stderr_buffer[..read], |line| {
+                                output.write_stderr_line(line)
+                            }),
                         }
                     },
                     _ = cancellation_token.cancelled(), if !stderr_complete && !stdout_complete => {
@@ -77,17 +160,36 @@ impl Restic {
                 }
             }
 
+            output.finish().await?;
             let status = process.wait().await?;
             Ok(status)
         };
 
-        let status = start().await.map_err(ResticError::FailedToExecute)?;
-        let code = status.code().ok_or(ResticError::Killed)?;
+        Self::map_status(start().await)
+    }
 
-        match map_exit_code_to_error(code) {
-            Ok(_) => Ok(()),
-            Err(e) => Err(e),
-        }
+    fn spawn(&self, arguments: ArgumentsBuilder, has_input: bool) -> Result<Child, io::Error> {
+        let binary_path = Self::get_binary_path()?;
+        let arguments = arguments.build();
+        info!("Executing restic command: '{binary_path:?} {arguments:?}'");
+        Command::new(binary_path)
+            .args(arguments)
+            .stdin(match has_input {
+                true => Stdio::piped(),
+                false => Stdio::null(),
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .envs(self.config.environment.clone())
+            .creation_flags(CREATE_NEW_PROCESS_GROUP | CREATE_NEW_CONSOLE)
+            .kill_on_drop(true)
+            .spawn()
+    }
+
+    fn map_status(status: Result<ExitStatus, io::Error>) -> Result<(), ResticError> {
+        let status = status.map_err(ResticError::FailedToExecute)?;
+        let code = status.code().ok_or(ResticError::Killed)?;
+        map_exit_code_to_error(code)
     }
 
     fn get_binary_path() -> Result<OsString, io::Error> {
@@ -104,6 +206,22 @@ impl Restic {
     pub(crate) async fn exec_json<P, F>(
         &self,
         arguments: ArgumentsBuilder,
+        on_message: F,
+        cancellation_token: &CancellationToken,
+    ) -> Result<(), ResticError>
+    where
+        P: ResticMessage,
+        F: FnMut(P),
+    {
+        self.exec_json_with_input(arguments, None, on_message, cancellation_token)
+            .await
+    }
+
+    /// Same as `exec_json`, but writes the input to the stdin of restic.
+    pub(crate) async fn exec_json_with_input<P, F>(
+        &self,
+        arguments: ArgumentsBuilder,
+        input: Option<ResticInput>,
         mut on_message: F,
         cancellation_token: &CancellationToken,
     ) -> Result<(), ResticError>
@@ -111,8 +229,9 @@ impl Restic {
         P: ResticMessage,
         F: FnMut(P),
     {
-        self.exec(
+        self.exec_with_input(
             arguments.with_flag("json"),
+            input,
             |line, output_type| {
                 if line.is_empty() {
                     return;
@@ -134,6 +253,119 @@ impl Restic {
     }
 }
 
+/// Where the output of restic goes while it runs.
+trait ProcessOutput {
+    /// Called with each chunk of stdout as read, chunks do not follow line breaks.
+    async fn write_stdout(&mut self, chunk: &[u8]) -> io::Result<()>;
+
+    fn write_stderr_line(&mut self, line: String);
+
+    /// Called once restic stopped writing.
+    async fn finish(&mut self) -> io::Result<()>;
+}
+
+/// Passes both stdout and stderr line by line.
+struct LineOutput<F> {
+    on_message: F,
+    stdout_lines: LineBuffer,
+}
+
+impl<F: FnMut(String, MessageOutputType)> ProcessOutput for LineOutput<F> {
+    async fn write_stdout(&mut self, chunk: &[u8]) -> io::Result<()> {
+        self.stdout_lines.push(chunk, |line| {
+            (self.on_message)(line, MessageOutputType::Stdout)
+        });
+        Ok(())
+    }
+
+    fn write_stderr_line(&mut self, line: String) {
+        (self.on_message)(line, MessageOutputType::Stderr)
+    }
+
+    async fn finish(&mut self) -> io::Result<()> {
+        self.stdout_lines
+            .finish(|line| (self.on_message)(line, MessageOutputType::Stdout));
+        Ok(())
+    }
+}
+
+/// Copies stdout as is to a writer.
+struct WriterOutput<W, F> {
+    output: W,
+    on_stderr: F,
+}
+
+impl<W: AsyncWrite + Unpin, F: FnMut(String)> ProcessOutput for WriterOutput<W, F> {
+    async fn write_stdout(&mut self, chunk: &[u8]) -> io::Result<()> {
+        self.output.write_all(chunk).await
+    }
+
+    fn write_stderr_line(&mut self, line: String) {
+        (self.on_stderr)(line)
+    }
+
+    async fn finish(&mut self) -> io::Result<()> {
+        self.output.flush().await
+    }
+}
+
+/// Passes stdout in chunks as read.
+struct ChunkOutput<F, E> {
+    on_stdout: F,
+    on_stderr: E,
+}
+
+impl<F: FnMut(&[u8]), E: FnMut(String)> ProcessOutput for ChunkOutput<F, E> {
+    async fn write_stdout(&mut self, chunk: &[u8]) -> io::Result<()> {
+        (self.on_stdout)(chunk);
+        Ok(())
+    }
+
+    fn write_stderr_line(&mut self, line: String) {
+        (self.on_stderr)(line)
+    }
+
+    async fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Splits output read in chunks into lines without their line break, invalid UTF-8 is replaced instead of failing.
+#[derive(Default)]
+struct LineBuffer {
+    pending: Vec<u8>,
+}
+
+impl LineBuffer {
+    fn push(&mut self, mut chunk: &[u8], mut on_line: impl FnMut(String)) {
+        while let Some(end) = chunk.iter().position(|&byte| byte == b'\n') {
+            self.pending.extend_from_slice(&chunk[..end]);
+            on_line(self.take_line());
+            chunk = &chunk[end + 1..];
+        }
+        self.pending.extend_from_slice(chunk);
+    }
+
+    /// Passes the last line, when the output did not end with a line break.
+    fn finish(&mut self, mut on_line: impl FnMut(String)) {
+        if !self.pending.is_empty() {
+            on_line(self.take_line());
+        }
+    }
+
+    fn take_line(&mut self) -> String {
+        if self.pending.last() == Some(&b'\r') {
+            self.pending.pop();
+        }
+        let line = String::from_utf8_lossy(&self.pending).into_owned();
+        self.pending.clear();
+        line
+    }
+}
+
+/// Data written to the stdin of restic, e.g. for `backup --stdin`.
+pub type ResticInput = Box<dyn AsyncRead + Unpin + Send>;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MessageOutputType {
     Stdout,
@@ -148,3 +380,44 @@ impl Display for MessageOutputType {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(chunks: &[&[u8]]) -> Vec<String> {
+        let mut buffer = LineBuffer::default();
+        let mut lines = Vec::new();
+        for chunk in chunks {
+            buffer.push(chunk, |line| lines.push(line));
+        }
+        buffer.finish(|line| lines.push(line));
+        lines
+    }
+
+    #[test]
+    fn can_split_lines_across_chunks() {
+        assert_eq!(
+            split(&[b"{\"a\":", b"1}\n{\"b\"", b":2}\n"]),
+            vec![r#"{"a":1}"#, r#"{"b":2}"#]
+        );
+    }
+
+    #[test]
+    fn can_split_crlf_lines() {
+        assert_eq!(split(&[b"first\r\nsecond\r\n"]), vec!["first", "second"]);
+    }
+
+    #[test]
+    fn keeps_empty_lines_and_the_last_line_without_line_break() {
+        assert_eq!(split(&[b"first\n\nlast"]), vec!["first", "", "last"]);
+    }
+
+    #[test]
+    fn replaces_invalid_utf8() {
+        assert_eq!(
+            split(&[b"caf\xe9\n", "café\n".as_bytes()]),
+            vec!["caf\u{FFFD}", "café"]
+        );
+    }
+}
@@ -0,0 +1,252 @@
+use crate::errors::ResticError;
+use crate::messages::{FindSnapshotMatches, Node};
+use crate::{ArgumentsBuilder, BuilderValue, Restic};
+use log::debug;
+use tokio_util::sync::CancellationToken;
+
+impl Restic {
+    /// Finds the items matching the pattern (e.g. "*.docx") in all snapshots, passing each one with the ID of its
+    /// snapshot to `on_match`.
+    ///
+    /// Restic prints the results as one JSON array without line breaks, it is parsed while restic runs, one snapshot
+    /// at a time. Restic prints the ID of a snapshot after its matches, so the matches of one snapshot are held until
+    /// it ends, the options can narrow the snapshots searched.
+    pub async fn find<F>(
+        &self,
+        pattern: &str,
+        options: FindOptions,
+        mut on_match: F,
+        cancellation_token: &CancellationToken,
+    ) -> Result<(), ResticError>
+    where
+        F: FnMut(&str, Node),
+    {
+        let mut splitter = JsonArraySplitter::default();
+        let mut parse_error = None;
+        self.exec_with_chunks(
+            options
+                .builder
+                .with_flag("json")
+                .with_trailing_values([pattern]),
+            |chunk| {
+                splitter.push(chunk, |element| {
+                    if parse_error.is_some() {
+                        return;
+                    }
+                    match serde_json::from_slice::<FindSnapshotMatches>(element) {
+                        Ok(result) => {
+                            for node in result.matches {
+                                on_match(&result.snapshot, node);
+                            }
+                        }
+                        Err(e) => parse_error = Some(e),
+                    }
+                })
+            },
+            |line| debug!("Restic stderr: {line}"),
+            cancellation_token,
+        )
+        .await?;
+
+        match parse_error {
+            Some(e) => Err(ResticError::UnexpectedResponse(format!(
+                "Failed to parse find JSON: {e}"
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Splits a JSON array read in chunks into its elements, without holding more than the current element.
+#[derive(Default)]
+struct JsonArraySplitter {
+    /// Nesting depth, 1 inside the array.
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    element: Vec<u8>,
+}
+
+impl JsonArraySplitter {
+    fn push(&mut self, chunk: &[u8], mut on_element: impl FnMut(&[u8])) {
+        for &byte in chunk {
+            match self.depth {
+                // Anything before the array, or after it, is not part of the result.
+                0 => {
+                    if byte == b'[' {
+                        self.depth = 1;
+                    }
+                }
+                1 => match byte {
+                    b']' => self.depth = 0,
+                    b'{' | b'[' => {
+                        self.element.push(byte);
+                        self.depth = 2;
+                    }
+                    _ => {}
+                },
+                _ => {
+                    self.element.push(byte);
+                    if self.in_string {
+                        match (self.escaped, byte) {
+                            (true, _) => self.escaped = false,
+                            (false, b'\\') => self.escaped = true,
+                            (false, b'"') => self.in_string = false,
+                            _ => {}
+                        }
+                        continue;
+                    }
+                    match byte {
+                        b'"' => self.in_string = true,
+                        b'{' | b'[' => self.depth += 1,
+                        b'}' | b']' => {
+                            self.depth -= 1;
+                            if self.depth == 1 {
+                                on_element(&self.element);
+                                self.element.clear();
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Options for the `restic find` command.
+#[derive(Debug, Clone)]
+pub struct FindOptions {
+    builder: ArgumentsBuilder,
+}
+
+impl Default for FindOptions {
+    fn default() -> Self {
+        Self {
+            builder: ArgumentsBuilder::new().with_verb("find"),
+        }
+    }
+}
+
+impl FindOptions {
+    /// Creates a default `FindOptions` instance, searching all snapshots.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the `--ignore-case` flag.
+    ///
+    /// Ignore case for the pattern.
+    pub fn ignore_case(self) -> Self {
+        self.with_flag("ignore-case")
+    }
+
+    /// Sets the `--snapshot` flag.
+    ///
+    /// Only search this snapshot, can be repeated.
+    pub fn snapshot(self, snapshot_id: &str) -> Self {
+        self.with_flag_and_value("snapshot", snapshot_id)
+    }
+
+    /// Sets the `--newest` flag.
+    ///
+    /// Only search snapshots older than this time, e.g. "2025-03-31 22:00:00".
+    pub fn newest(self, value: &str) -> Self {
+        self.with_flag_and_value("newest", value)
+    }
+
+    /// Sets the `--oldest` flag.
+    ///
+    /// Only search snapshots newer than this time.
+    pub fn oldest(self, value: &str) -> Self {
+        self.with_flag_and_value("oldest", value)
+    }
+
+    /// Sets the `--host` flag.
+    ///
+    /// Only consider snapshots for this host.
+    pub fn host(self, value: &str) -> Self {
+        self.with_flag_and_value("host", value)
+    }
+
+    /// Sets the `--tag` flag.
+    ///
+    /// Only consider snapshots including the given tags.
+    pub fn tag(self, value: &str) -> Self {
+        self.with_flag_and_value("tag", value)
+    }
+
+    /// Sets the `--path` flag.
+    ///
+    /// Only consider snapshots including this (absolute) path.
+    pub fn path(self, value: &str) -> Self {
+        self.with_flag_and_value("path", value)
+    }
+
+    /// Adds a flag without a value.
+    pub fn with_flag(mut self, name: &str) -> Self {
+        self.builder = self.builder.with_flag(name);
+        self
+    }
+
+    /// Adds a flag with a value.
+    pub fn with_flag_and_value<V: BuilderValue>(mut self, name: &str, value: V) -> Self {
+        self.builder = self.builder.with_flag_and_value(name, value);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(chunks: &[&str]) -> Vec<String> {
+        let mut splitter = JsonArraySplitter::default();
+        let mut elements = Vec::new();
+        for chunk in chunks {
+            splitter.push(chunk.as_bytes(), |element| {
+                elements.push(String::from_utf8(element.to_vec()).unwrap())
+            });
+        }
+        elements
+    }
+
+    #[test]
+    fn when_no_output_then_no_elements() {
+        assert!(split(&[]).is_empty());
+        assert!(split(&["[]\n"]).is_empty());
+    }
+
+    #[test]
+    fn splits_elements_across_chunks() {
+        assert_eq!(
+            split(&[
+                r#"[{"matches":[{"path":"/a"}],"hits":1,"snap"#,
+                r#"shot":"1234"},{"matches":null,"#,
+                r#""hits":0,"snapshot":"5678"}]"#
+            ]),
+            vec![
+                r#"{"matches":[{"path":"/a"}],"hits":1,"snapshot":"1234"}"#,
+                r#"{"matches":null,"hits":0,"snapshot":"5678"}"#
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_brackets_and_escaped_quotes_in_strings() {
+        let element = r#"{"matches":[{"path":"/a \"[}]\\"}],"hits":1,"snapshot":"1234"}"#;
+
+        assert_eq!(split(&[&format!("[{element}]")]), vec![element]);
+    }
+
+    #[test]
+    fn elements_parse_as_snapshot_matches() {
+        let elements = split(&[
+            r#"[{"matches":[{"path":"/home/user/notes.txt","type":"file","size":12}],"hits":1,"snapshot":"1234"}]"#,
+        ]);
+
+        let result: FindSnapshotMatches = serde_json::from_str(&elements[0]).unwrap();
+        assert_eq!(result.snapshot, "1234");
+        assert_eq!(result.matches[0].path, "/home/user/notes.txt");
+    }
+}
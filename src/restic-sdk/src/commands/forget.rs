@@ -1,23 +1,82 @@
 use crate::errors::ResticError;
+use crate::exec::MessageOutputType;
+use crate::messages::{ForgetGroup, PruneStats, Snapshot};
+use crate::prune::PruneOptions;
+use crate::retention::{ByteSize, MaxUnused, RetentionDuration, RetentionPolicy};
 use crate::{ArgumentsBuilder, BuilderValue, Restic};
 use log::debug;
 use tokio_util::sync::CancellationToken;
 
 impl Restic {
+    /// Removes the snapshots not kept by the policy, then prunes the repository when `ForgetOptions::prune` is set
+    /// and snapshots were removed.
+    ///
+    /// Restic prints nothing about prune when forget outputs JSON, so prune runs as a separate `restic prune`.
     pub async fn forget(
         &self,
         options: ForgetOptions,
         cancellation_token: &CancellationToken,
-    ) -> Result<(), ResticError> {
+    ) -> Result<ForgetResult, ResticError> {
+        let mut json = String::new();
         self.exec(
-            options.builder,
-            |string, output_type| {
-                debug!("Restic {output_type:?}: {string}");
+            options.builder.with_flag("json"),
+            |line, output_type| match output_type {
+                MessageOutputType::Stdout if line.starts_with('[') => json += &line,
+                _ => debug!("Restic {output_type}: {line}"),
             },
             cancellation_token,
         )
         .await?;
-        Ok(())
+
+        let groups = parse_forget_json(&json).map_err(|e| {
+            ResticError::UnexpectedResponse(format!("Failed to parse forget JSON: {e}"))
+        })?;
+        let mut result = ForgetResult {
+            groups,
+            prune: None,
+        };
+
+        if options.prune && result.removed().next().is_some() {
+            result.prune = self
+                .prune(options.prune_options, cancellation_token)
+                .await?
+                .stats;
+        }
+
+        Ok(result)
+    }
+}
+
+fn parse_forget_json(json: &str) -> Result<Vec<ForgetGroup>, serde_json::Error> {
+    // Nothing is printed when the repository has no snapshots.
+    if json.is_empty() {
+        return Ok(Vec::new());
+    }
+    serde_json::from_str(json)
+}
+
+/// The result of `forget`, per group of snapshots.
+#[derive(Debug, Clone)]
+pub struct ForgetResult {
+    pub groups: Vec<ForgetGroup>,
+    /// The statistics of prune, when it ran and restic printed them.
+    pub prune: Option<PruneStats>,
+}
+
+impl ForgetResult {
+    /// The snapshots kept in every group, with the rules that kept them.
+    pub fn kept(&self) -> impl Iterator<Item = (&Snapshot, &[String])> {
+        self.groups.iter().flat_map(|group| {
+            group
+                .keep
+                .iter()
+                .map(|snapshot| (snapshot, group.get_reasons(&snapshot.id)))
+        })
+    }
+
+    /// The snapshots removed from every group.
+    pub fn removed(&self) -> impl Iterator<Item = &Snapshot> {
+        self.groups.iter().flat_map(|group| group.remove.iter())
     }
 }
 
@@ -25,12 +84,16 @@ impl Restic {
 #[derive(Debug, Clone)]
 pub struct ForgetOptions {
     builder: ArgumentsBuilder,
+    prune: bool,
+    prune_options: PruneOptions,
 }
 
 impl Default for ForgetOptions {
     fn default() -> Self {
         Self {
             builder: ArgumentsBuilder::new().with_verb("forget"),
+            prune: false,
+            prune_options: PruneOptions::default(),
         }
     }
 }
@@ -93,32 +156,42 @@ impl ForgetOptions {
     /// Sets the `--keep-within` flag.
     ///
     /// Keep snapshots newer than the given duration (e.g., "1y5m7d2h").
-    pub fn keep_within(self, value: &str) -> Self {
+    pub fn keep_within(self, value: RetentionDuration) -> Self {
         self.with_flag_and_value("keep-within", value)
     }
 
     /// Sets the `--keep-within-hourly` flag.
-    pub fn keep_within_hourly(self, value: u32) -> Self {
+    ///
+    /// Keep hourly snapshots newer than the given duration.
+    pub fn keep_within_hourly(self, value: RetentionDuration) -> Self {
         self.with_flag_and_value("keep-within-hourly", value)
     }
 
     /// Sets the `--keep-within-daily` flag.
-    pub fn keep_within_daily(self, value: u32) -> Self {
+    ///
+    /// Keep daily snapshots newer than the given duration.
+    pub fn keep_within_daily(self, value: RetentionDuration) -> Self {
         self.with_flag_and_value("keep-within-daily", value)
     }
 
     /// Sets the `--keep-within-weekly` flag.
-    pub fn keep_within_weekly(self, value: u32) -> Self {
+    ///
+    /// Keep weekly snapshots newer than the given duration.
+    pub fn keep_within_weekly(self, value: RetentionDuration) -> Self {
         self.with_flag_and_value("keep-within-weekly", value)
     }
 
     /// Sets the `--keep-within-monthly` flag.
-    pub fn keep_within_monthly(self, value: u32) -> Self {
+    ///
+    /// Keep monthly snapshots newer than the given duration.
+    pub fn keep_within_monthly(self, value: RetentionDuration) -> Self {
         self.with_flag_and_value("keep-within-monthly", value)
     }
 
     /// Sets the `--keep-within-yearly` flag.
-    pub fn keep_within_yearly(self, value: u32) -> Self {
+    ///
+    /// Keep yearly snapshots newer than the given duration.
+    pub fn keep_within_yearly(self, value: RetentionDuration) -> Self {
         self.with_flag_and_value("keep-within-yearly", value)
     }
 
@@ -129,6 +202,45 @@ impl ForgetOptions {
         self.with_flag_and_value("keep-tag", value)
     }
 
+    /// Sets the flags of every rule of the policy.
+    pub fn policy(mut self, policy: &RetentionPolicy) -> Self {
+        let counts = [
+            ("keep-last", policy.keep_last),
+            ("keep-hourly", policy.keep_hourly),
+            ("keep-daily", policy.keep_daily),
+            ("keep-weekly", policy.keep_weekly),
+            ("keep-monthly", policy.keep_monthly),
+            ("keep-yearly", policy.keep_yearly),
+        ];
+        for (name, count) in counts {
+            if let Some(count) = count {
+                self = self.with_flag_and_value(name, count);
+            }
+        }
+
+        let durations = [
+            ("keep-within", policy.keep_within),
+            ("keep-within-hourly", policy.keep_within_hourly),
+            ("keep-within-daily", policy.keep_within_daily),
+            ("keep-within-weekly", policy.keep_within_weekly),
+            ("keep-within-monthly", policy.keep_within_monthly),
+            ("keep-within-yearly", policy.keep_within_yearly),
+        ];
+        for (name, duration) in durations {
+            if let Some(duration) = duration {
+                self = self.with_flag_and_value(name, duration);
+            }
+        }
+
+        for tags in policy.tag_lists() {
+            self = self.keep_tag(tags);
+        }
+        if policy.unsafe_allow_remove_all {
+            self = self.unsafe_allow_remove_all();
+        }
+        self
+    }
+
     /// Sets the `--unsafe-allow-remove-all` flag.
     ///
     /// Allow deleting all snapshots of a snapshot group.
@@ -145,7 +257,8 @@ impl ForgetOptions {
 
     /// Sets the `--tag` flag.
     ///
-    /// Only consider snapshots including the given tags.
+    /// Only consider snapshots including the given tags, separated by comma. When set more than once, snapshots
+    /// matching any of the values are considered, an empty value matches snapshots without tags.
     pub fn tag(self, value: &str) -> Self {
         self.with_flag_and_value("tag", value)
     }
@@ -166,58 +279,66 @@ impl ForgetOptions {
 
     /// Sets the `--dry-run` flag.
     ///
-    /// Do not delete anything, just print what would be done.
-    pub fn dry_run(self) -> Self {
+    /// Do not delete anything, just print what would be done. Also applies to prune.
+    pub fn dry_run(mut self) -> Self {
+        self.prune_options = self.prune_options.dry_run();
         self.with_flag("dry-run")
     }
 
-    /// Sets the `--prune` flag.
+    /// Runs `prune` after forget, when snapshots have been removed.
     ///
-    /// Automatically run the 'prune' command if snapshots have been removed.
-    pub fn prune(self) -> Self {
-        self.with_flag("prune")
+    /// Same as the `--prune` flag, the `--max-unused`, `--max-repack-size` and `--repack-*` flags apply to prune.
+    pub fn prune(mut self) -> Self {
+        self.prune = true;
+        self
     }
 
     /// Sets the `--max-unused` flag.
     ///
     /// Tolerate given limit of unused data (e.g., "5%", "10G", "unlimited").
-    pub fn max_unused(self, value: &str) -> Self {
-        self.with_flag_and_value("max-unused", value)
+    pub fn max_unused(mut self, value: MaxUnused) -> Self {
+        self.prune_options = self.prune_options.max_unused(value);
+        self
     }
 
     /// Sets the `--max-repack-size` flag.
     ///
     /// Stop after repacking this much data in total.
-    pub fn max_repack_size(self, value: &str) -> Self {
-        self.with_flag_and_value("max-repack-size", value)
+    pub fn max_repack_size(mut self, value: ByteSize) -> Self {
+        self.prune_options = self.prune_options.max_repack_size(value);
+        self
     }
 
     /// Sets the `--repack-cacheable-only` flag.
     ///
     /// Only repack packs which are cacheable.
-    pub fn repack_cacheable_only(self) -> Self {
-        self.with_flag("repack-cacheable-only")
+    pub fn repack_cacheable_only(mut self) -> Self {
+        self.prune_options = self.prune_options.repack_cacheable_only();
+        self
     }
 
     /// Sets the `--repack-small` flag.
     ///
     /// Repack pack files below 80% of target pack size.
-    pub fn repack_small(self) -> Self {
-        self.with_flag("repack-small")
+    pub fn repack_small(mut self) -> Self {
+        self.prune_options = self.prune_options.repack_small();
+        self
     }
 
     /// Sets the `--repack-uncompressed` flag.
     ///
     /// Repack all uncompressed data.
-    pub fn repack_uncompressed(self) -> Self {
-        self.with_flag("repack-uncompressed")
+    pub fn repack_uncompressed(mut self) -> Self {
+        self.prune_options = self.prune_options.repack_uncompressed();
+        self
     }
 
     /// Sets the `--repack-smaller-than` flag.
     ///
     /// Pack below-limit packfiles (e.g., "10M").
-    pub fn repack_smaller_than(self, value: &str) -> Self {
-        self.with_flag_and_value("repack-smaller-than", value)
+    pub fn repack_smaller_than(mut self, value: ByteSize) -> Self {
+        self.prune_options = self.prune_options.repack_smaller_than(value);
+        self
     }
 
     /// Adds a flag without a value.
@@ -232,3 +353,60 @@ impl ForgetOptions {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn when_no_output_then_no_groups() {
+        let groups = parse_forget_json("").expect("should parse");
+
+        assert!(groups.is_empty());
+    }
+
+    #[test]
+    fn can_apply_policy() {
+        let policy = RetentionPolicy {
+            keep_last: Some(3),
+            keep_within_daily: Some("7d".parse().unwrap()),
+            keep_tags: vec!["pinned".to_owned(), "manual".to_owned()],
+            ..Default::default()
+        };
+
+        let options = ForgetOptions::new().policy(&policy);
+
+        assert_eq!(
+            options.builder.build(),
+            vec![
+                "forget",
+                "--keep-last",
+                "3",
+                "--keep-within-daily",
+                "7d",
+                "--keep-tag",
+                "pinned",
+                "--keep-tag",
+                "manual"
+            ]
+        );
+    }
+
+    #[test]
+    fn when_dry_run_and_prune_then_prune_is_dry_run() {
+        let options = ForgetOptions::new()
+            .keep_last(1)
+            .prune()
+            .max_unused(MaxUnused::Percent(5.0))
+            .dry_run();
+
+        assert_eq!(
+            options.builder.build(),
+            vec!["forget", "--keep-last", "1", "--dry-run"]
+        );
+        assert_eq!(
+            options.prune_options.builder.build(),
+            vec!["prune", "--max-unused", "5%", "--dry-run"]
+        );
+    }
+}
@@ -132,6 +132,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_parse_lock_json() {
         let json = r#"{
             "time": "2025-07-06T18:56:28.8860478-05:00",
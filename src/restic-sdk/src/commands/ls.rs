@@ -0,0 +1,98 @@
+use crate::errors::ResticError;
+use crate::exec::MessageOutputType;
+use crate::messages::{LsMessage, Node, Snapshot};
+use crate::{ArgumentsBuilder, BuilderValue, Restic};
+use log::{debug, warn};
+use tokio_util::sync::CancellationToken;
+
+impl Restic {
+    /// Lists the items of the snapshot (an ID or "latest"), passing each item to `on_node` as restic prints it, so
+    /// a huge directory is never held in memory. Returns the listed snapshot.
+    pub async fn ls<F>(
+        &self,
+        snapshot_id: &str,
+        options: LsOptions,
+        mut on_node: F,
+        cancellation_token: &CancellationToken,
+    ) -> Result<Snapshot, ResticError>
+    where
+        F: FnMut(Node),
+    {
+        let mut snapshot = None;
+        let arguments = options
+            .builder
+            .with_flag("json")
+            .with_trailing_values(std::iter::once(snapshot_id.to_owned()).chain(options.paths));
+
+        self.exec(
+            arguments,
+            |line, output_type| match output_type {
+                MessageOutputType::Stdout => match serde_json::from_str(&line) {
+                    Ok(LsMessage::Snapshot(listed)) => snapshot = Some(listed),
+                    Ok(LsMessage::Node(node)) => on_node(node),
+                    Err(e) => warn!("Failed to parse ls line '{line}'. Error: {e}"),
+                },
+                MessageOutputType::Stderr => debug!("Restic {output_type}: {line}"),
+            },
+            cancellation_token,
+        )
+        .await?;
+
+        snapshot.ok_or_else(|| ResticError::UnexpectedResponse("ls printed no snapshot".to_owned()))
+    }
+}
+
+/// Options for the `restic ls` command.
+#[derive(Debug, Clone)]
+pub struct LsOptions {
+    builder: ArgumentsBuilder,
+    paths: Vec<String>,
+}
+
+impl Default for LsOptions {
+    fn default() -> Self {
+        Self {
+            builder: ArgumentsBuilder::new().with_verb("ls"),
+            paths: Vec::new(),
+        }
+    }
+}
+
+impl LsOptions {
+    /// Creates a default `LsOptions` instance, listing the whole snapshot.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only lists the items directly in this directory, or in all its subdirectories with `recursive`.
+    pub fn path(mut self, path: &str) -> Self {
+        self.paths.push(path.to_owned());
+        self
+    }
+
+    /// Sets the `--recursive` flag.
+    ///
+    /// Include the subdirectories of the given paths.
+    pub fn recursive(self) -> Self {
+        self.with_flag("recursive")
+    }
+
+    /// Sets the `--host` flag.
+    ///
+    /// Only consider snapshots for this host, when the snapshot is "latest".
+    pub fn host(self, value: &str) -> Self {
+        self.with_flag_and_value("host", value)
+    }
+
+    /// Adds a flag without a value.
+    pub fn with_flag(mut self, name: &str) -> Self {
+        self.builder = self.builder.with_flag(name);
+        self
+    }
+
+    /// Adds a flag with a value.
+    pub fn with_flag_and_value<V: BuilderValue>(mut self, name: &str, value: V) -> Self {
+        self.builder = self.builder.with_flag_and_value(name, value);
+        self
+    }
+}
@@ -1,8 +1,16 @@
 pub mod backup;
 pub mod cat;
+pub mod diff;
+pub mod dump;
 pub mod exec;
+pub mod find;
 pub mod forget;
 pub mod init;
 pub mod list_locks;
+pub mod ls;
+pub mod prune;
+pub mod snapshots;
+pub mod stats;
+pub mod tag;
 pub mod unlock;
 pub mod version;
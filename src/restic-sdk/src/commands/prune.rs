@@ -0,0 +1,127 @@
+use crate::errors::ResticError;
+use crate::messages::PruneStats;
+use crate::retention::{ByteSize, MaxUnused};
+use crate::{ArgumentsBuilder, BuilderValue, Restic};
+use log::{debug, info, warn};
+use tokio_util::sync::CancellationToken;
+
+impl Restic {
+    /// Removes unreferenced data from the repository.
+    ///
+    /// Restic has no JSON output for prune, the statistics are parsed from its text output.
+    pub async fn prune(
+        &self,
+        options: PruneOptions,
+        cancellation_token: &CancellationToken,
+    ) -> Result<PruneResult, ResticError> {
+        let mut lines = Vec::new();
+        self.exec(
+            options.builder,
+            |line, output_type| {
+                debug!("Restic {output_type}: {line}");
+                lines.push(line);
+            },
+            cancellation_token,
+        )
+        .await?;
+
+        let stats = PruneStats::parse(lines.iter().map(String::as_str));
+        match &stats {
+            Some(stats) => info!(
+                "Prune removed {} blob(s), freeing {} bytes.",
+                stats.blobs_pruned, stats.bytes_pruned
+            ),
+            None => warn!("Prune completed without printing statistics."),
+        }
+
+        Ok(PruneResult { stats })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PruneResult {
+    /// The statistics restic printed, `None` when it printed none.
+    pub stats: Option<PruneStats>,
+}
+
+/// Options for the `restic prune` command.
+#[derive(Debug, Clone)]
+pub struct PruneOptions {
+    pub(crate) builder: ArgumentsBuilder,
+}
+
+impl Default for PruneOptions {
+    fn default() -> Self {
+        Self {
+            builder: ArgumentsBuilder::new().with_verb("prune"),
+        }
+    }
+}
+
+impl PruneOptions {
+    /// Creates a default `PruneOptions` instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the `--dry-run` flag.
+    ///
+    /// Do not modify the repository, just print what would be done.
+    pub fn dry_run(self) -> Self {
+        self.with_flag("dry-run")
+    }
+
+    /// Sets the `--max-unused` flag.
+    ///
+    /// Tolerate given limit of unused data (e.g., "5%", "10G", "unlimited").
+    pub fn max_unused(self, value: MaxUnused) -> Self {
+        self.with_flag_and_value("max-unused", value)
+    }
+
+    /// Sets the `--max-repack-size` flag.
+    ///
+    /// Stop after repacking this much data in total.
+    pub fn max_repack_size(self, value: ByteSize) -> Self {
+        self.with_flag_and_value("max-repack-size", value)
+    }
+
+    /// Sets the `--repack-cacheable-only` flag.
+    ///
+    /// Only repack packs which are cacheable.
+    pub fn repack_cacheable_only(self) -> Self {
+        self.with_flag("repack-cacheable-only")
+    }
+
+    /// Sets the `--repack-small` flag.
+    ///
+    /// Repack pack files below 80% of target pack size.
+    pub fn repack_small(self) -> Self {
+        self.with_flag("repack-small")
+    }
+
+    /// Sets the `--repack-uncompressed` flag.
+    ///
+    /// Repack all uncompressed data.
+    pub fn repack_uncompressed(self) -> Self {
+        self.with_flag("repack-uncompressed")
+    }
+
+    /// Sets the `--repack-smaller-than` flag.
+    ///
+    /// Pack below-limit packfiles (e.g., "10M").
+    pub fn repack_smaller_than(self, value: ByteSize) -> Self {
+        self.with_flag_and_value("repack-smaller-than", value)
+    }
+
+    /// Adds a flag without a value.
+    pub fn with_flag(mut self, name: &str) -> Self {
+        self.builder = self.builder.with_flag(name);
+        self
+    }
+
+    /// Adds a flag with a value.
+    pub fn with_flag_and_value<V: BuilderValue>(mut self, name: &str, value: V) -> Self {
+        self.builder = self.builder.with_flag_and_value(name, value);
+        self
+    }
+}
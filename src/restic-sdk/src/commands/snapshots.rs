@@ -0,0 +1,131 @@
+use crate::errors::ResticError;
+use crate::exec::MessageOutputType;
+use crate::messages::Snapshot;
+use crate::{ArgumentsBuilder, BuilderValue, Restic};
+use log::debug;
+use tokio_util::sync::CancellationToken;
+
+impl Restic {
+    /// Lists the snapshots of the repository, oldest first.
+    pub async fn snapshots(
+        &self,
+        options: SnapshotsOptions,
+        cancellation_token: &CancellationToken,
+    ) -> Result<Vec<Snapshot>, ResticError> {
+        let mut json = String::new();
+        self.exec(
+            options
+                .builder
+                .with_flag("json")
+                .with_trailing_values(options.snapshot_ids),
+            |line, output_type| match output_type {
+                MessageOutputType::Stdout if line.starts_with('[') => json = line,
+                _ => debug!("Restic {output_type}: {line}"),
+            },
+            cancellation_token,
+        )
+        .await?;
+
+        serde_json::from_str(&json).map_err(|e| {
+            ResticError::UnexpectedResponse(format!("Failed to parse snapshots JSON: {e}"))
+        })
+    }
+}
+
+/// Options for the `restic snapshots` command.
+#[derive(Debug, Clone)]
+pub struct SnapshotsOptions {
+    builder: ArgumentsBuilder,
+    snapshot_ids: Vec<String>,
+}
+
+impl Default for SnapshotsOptions {
+    fn default() -> Self {
+        Self {
+            builder: ArgumentsBuilder::new().with_verb("snapshots"),
+            snapshot_ids: Vec::new(),
+        }
+    }
+}
+
+impl SnapshotsOptions {
+    /// Creates a default `SnapshotsOptions` instance, listing all snapshots.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only lists this snapshot, an ID or "latest".
+    pub fn snapshot(mut self, snapshot_id: &str) -> Self {
+        self.snapshot_ids.push(snapshot_id.to_owned());
+        self
+    }
+
+    /// Sets the `--latest` flag.
+    ///
+    /// Only lists the last n snapshots of each host and paths.
+    pub fn latest(self, count: u32) -> Self {
+        self.with_flag_and_value("latest", count)
+    }
+
+    /// Sets the `--host` flag.
+    ///
+    /// Only consider snapshots for this host.
+    pub fn host(self, value: &str) -> Self {
+        self.with_flag_and_value("host", value)
+    }
+
+    /// Sets the `--tag` flag.
+    ///
+    /// Only consider snapshots including the given tags.
+    pub fn tag(self, value: &str) -> Self {
+        self.with_flag_and_value("tag", value)
+    }
+
+    /// Sets the `--path` flag.
+    ///
+    /// Only consider snapshots including this (absolute) path.
+    pub fn path(self, value: &str) -> Self {
+        self.with_flag_and_value("path", value)
+    }
+
+    /// Adds a flag without a value.
+    pub fn with_flag(mut self, name: &str) -> Self {
+        self.builder = self.builder.with_flag(name);
+        self
+    }
+
+    /// Adds a flag with a value.
+    pub fn with_flag_and_value<V: BuilderValue>(mut self, name: &str, value: V) -> Self {
+        self.builder = self.builder.with_flag_and_value(name, value);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_build_arguments() {
+        let options = SnapshotsOptions::new()
+            .tag("job:daily")
+            .latest(2)
+            .snapshot("1234");
+
+        assert_eq!(
+            options
+                .builder
+                .with_trailing_values(options.snapshot_ids)
+                .build(),
+            vec![
+                "snapshots",
+                "--tag",
+                "job:daily",
+                "--latest",
+                "2",
+                "--",
+                "1234"
+            ]
+        );
+    }
+}
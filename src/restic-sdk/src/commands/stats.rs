@@ -0,0 +1,155 @@
+use crate::errors::ResticError;
+use crate::exec::MessageOutputType;
+use crate::messages::RepositoryStats;
+use crate::{ArgumentsBuilder, BuilderValue, Restic};
+use log::debug;
+use std::fmt::{Display, Formatter};
+use tokio_util::sync::CancellationToken;
+
+impl Restic {
+    /// Computes statistics of the repository, or of the selected snapshots.
+    pub async fn stats(
+        &self,
+        options: StatsOptions,
+        cancellation_token: &CancellationToken,
+    ) -> Result<RepositoryStats, ResticError> {
+        let mut json = None;
+        self.exec(
+            options.builder.with_flag("json"),
+            |line, output_type| match output_type {
+                MessageOutputType::Stdout if line.starts_with('{') => json = Some(line),
+                _ => debug!("Restic {output_type}: {line}"),
+            },
+            cancellation_token,
+        )
+        .await?;
+
+        let json = json.ok_or_else(|| {
+            ResticError::UnexpectedResponse("stats printed no statistics".to_owned())
+        })?;
+        serde_json::from_str(&json).map_err(|e| {
+            ResticError::UnexpectedResponse(format!("Failed to parse stats JSON: {e}"))
+        })
+    }
+}
+
+/// What `stats` counts, see https://restic.readthedocs.io/en/stable/manual_rest.html#getting-information-about-repository-data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StatsMode {
+    /// The size of the files when restored, the default of restic.
+    RestoreSize,
+    /// The size of the unique files, counted once by their contents.
+    FilesByContents,
+    /// The size of the data stored in the repository, after deduplication and compression.
+    RawData,
+    /// The size of the files counted by their unique blobs.
+    BlobsPerFile,
+}
+
+impl StatsMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StatsMode::RestoreSize => "restore-size",
+            StatsMode::FilesByContents => "files-by-contents",
+            StatsMode::RawData => "raw-data",
+            StatsMode::BlobsPerFile => "blobs-per-file",
+        }
+    }
+}
+
+impl Display for StatsMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl BuilderValue for StatsMode {
+    fn to_builder_value(&self) -> String {
+        self.as_str().to_owned()
+    }
+}
+
+/// Options for the `restic stats` command.
+#[derive(Debug, Clone)]
+pub struct StatsOptions {
+    builder: ArgumentsBuilder,
+}
+
+impl Default for StatsOptions {
+    fn default() -> Self {
+        Self {
+            builder: ArgumentsBuilder::new().with_verb("stats"),
+        }
+    }
+}
+
+impl StatsOptions {
+    /// Creates a default `StatsOptions` instance, the statistics of all snapshots in `restore-size` mode.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the `--mode` flag.
+    ///
+    /// What to count, restic defaults to `restore-size`.
+    pub fn mode(self, mode: StatsMode) -> Self {
+        self.with_flag_and_value("mode", mode)
+    }
+
+    /// Sets the `--host` flag.
+    ///
+    /// Only consider snapshots for this host.
+    pub fn host(self, value: &str) -> Self {
+        self.with_flag_and_value("host", value)
+    }
+
+    /// Sets the `--tag` flag.
+    ///
+    /// Only consider snapshots including the given tags.
+    pub fn tag(self, value: &str) -> Self {
+        self.with_flag_and_value("tag", value)
+    }
+
+    /// Sets the `--path` flag.
+    ///
+    /// Only consider snapshots including this (absolute) path.
+    pub fn path(self, value: &str) -> Self {
+        self.with_flag_and_value("path", value)
+    }
+
+    /// Only consider this snapshot, an ID or "latest".
+    pub fn snapshot(mut self, snapshot_id: &str) -> Self {
+        self.builder = self.builder.with_value(snapshot_id);
+        self
+    }
+
+    /// Adds a flag without a value.
+    pub fn with_flag(mut self, name: &str) -> Self {
+        self.builder = self.builder.with_flag(name);
+        self
+    }
+
+    /// Adds a flag with a value.
+    pub fn with_flag_and_value<V: BuilderValue>(mut self, name: &str, value: V) -> Self {
+        self.builder = self.builder.with_flag_and_value(name, value);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_build_arguments() {
+        let options = StatsOptions::new()
+            .mode(StatsMode::RawData)
+            .host("laptop")
+            .snapshot("latest");
+
+        assert_eq!(
+            options.builder.build(),
+            vec!["stats", "--mode", "raw-data", "--host", "laptop", "latest"]
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use crate::errors::ResticError;
+use crate::messages::{ResticTagMessage, TagChangedSnapshot};
+use crate::{ArgumentsBuilder, BuilderValue, Restic};
+use log::{debug, warn};
+use tokio_util::sync::CancellationToken;
+
+impl Restic {
+    /// Changes the tags of the snapshots (IDs or "latest"), all snapshots matching the filters of the options when
+    /// none are given. Returns the changed snapshots, restic replaces each one with a new snapshot (and ID).
+    pub async fn tag(
+        &self,
+        snapshot_ids: &[&str],
+        options: TagOptions,
+        cancellation_token: &CancellationToken,
+    ) -> Result<Vec<TagChangedSnapshot>, ResticError> {
+        let mut changed = Vec::new();
+        self.exec_json(
+            options
+                .builder
+                .with_trailing_values(snapshot_ids.iter().copied()),
+            |message: ResticTagMessage| match message {
+                ResticTagMessage::TagChangedSnapshot(message) => changed.push(message),
+                ResticTagMessage::TagSummary(summary) => {
+                    debug!(
+                        "Changed the tags of {} snapshot(s).",
+                        summary.changed_snapshots
+                    )
+                }
+                ResticTagMessage::ExitError(error) => {
+                    warn!(
+                        "Restic will exit with: {error} (code: {code})",
+                        error = error.message,
+                        code = error.code
+                    );
+                }
+            },
+            cancellation_token,
+        )
+        .await?;
+
+        Ok(changed)
+    }
+}
+
+/// Options for the `restic tag` command.
+#[derive(Debug, Clone)]
+pub struct TagOptions {
+    builder: ArgumentsBuilder,
+}
+
+impl Default for TagOptions {
+    fn default() -> Self {
+        Self {
+            builder: ArgumentsBuilder::new().with_verb("tag"),
+        }
+    }
+}
+
+impl TagOptions {
+    /// Creates a default `TagOptions` instance, which changes nothing until tags are added, removed or set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the `--add` flag.
+    ///
+    /// Tags to add to the snapshots, separated by comma.
+    pub fn add_tags(self, tags: &str) -> Self {
+        self.with_flag_and_value("add", tags)
+    }
+
+    /// Sets the `--remove` flag.
+    ///
+    /// Tags to remove from the snapshots, separated by comma.
+    pub fn remove_tags(self, tags: &str) -> Self {
+        self.with_flag_and_value("remove", tags)
+    }
+
+    /// Sets the `--set` flag.
+    ///
+    /// Tags replacing all tags of the snapshots, separated by comma.
+    pub fn set_tags(self, tags: &str) -> Self {
+        self.with_flag_and_value("set", tags)
+    }
+
+    /// Sets the `--host` flag.
+    ///
+    /// Only consider snapshots for this host.
+    pub fn host(self, value: &str) -> Self {
+        self.with_flag_and_value("host", value)
+    }
+
+    /// Sets the `--tag` flag.
+    ///
+    /// Only consider snapshots including the given tags.
+    pub fn tag(self, value: &str) -> Self {
+        self.with_flag_and_value("tag", value)
+    }
+
+    /// Sets the `--path` flag.
+    ///
+    /// Only consider snapshots including this (absolute) path.
+    pub fn path(self, value: &str) -> Self {
+        self.with_flag_and_value("path", value)
+    }
+
+    /// Adds a flag without a value.
+    pub fn with_flag(mut self, name: &str) -> Self {
+        self.builder = self.builder.with_flag(name);
+        self
+    }
+
+    /// Adds a flag with a value.
+    pub fn with_flag_and_value<V: BuilderValue>(mut self, name: &str, value: V) -> Self {
+        self.builder = self.builder.with_flag_and_value(name, value);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_build_arguments() {
+        let options = TagOptions::new()
+            .add_tags("pinned")
+            .add_tags("manual")
+            .remove_tags("old");
+
+        assert_eq!(
+            options.builder.with_trailing_values(["1234"]).build(),
+            vec![
+                "tag", "--add", "pinned", "--add", "manual", "--remove", "old", "--", "1234"
+            ]
+        );
+    }
+}
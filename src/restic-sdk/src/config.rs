@@ -21,6 +21,18 @@ impl ResticConfig {
         self.with_env_var("RESTIC_PASSWORD", password)
     }
 
+    /// File containing the password for the repository.
+    /// This will set the `RESTIC_PASSWORD_FILE` environment variable on the spawned process.
+    pub fn with_password_file(self, path: &str) -> Self {
+        self.with_env_var("RESTIC_PASSWORD_FILE", path)
+    }
+
+    /// Command that prints the password for the repository to stdout.
+    /// This will set the `RESTIC_PASSWORD_COMMAND` environment variable on the spawned process.
+    pub fn with_password_command(self, command: &str) -> Self {
+        self.with_env_var("RESTIC_PASSWORD_COMMAND", command)
+    }
+
     /// Concurrency for file reads.
     /// This will set the `RESTIC_READ_CONCURRENCY` environment variable on the spawned process.
     pub fn with_read_concurrency(self, concurrency: usize) -> Self {
@@ -11,6 +11,8 @@ pub enum ResticError {
     // Real errors from restic.
     #[error("restic exit(1): Command failed, see command help for more details")]
     GenericError,
+    #[error("restic exit(1): The command whose output is backed up failed: {0}")]
+    StdinCommandFailed(String),
     #[error("restic exit(2): Go runtime error")]
     GoRuntimeError,
     #[error("restic exit(3): Backup command could not read some source data")]
@@ -9,6 +9,7 @@ mod parsing;
 
 pub mod errors;
 pub mod messages;
+pub mod retention;
 
 pub use arg_builder::*;
 pub use client::*;
@@ -0,0 +1,116 @@
+use crate::messages::ExitError;
+use crate::restic_message;
+use serde::{Deserialize, Serialize};
+
+restic_message! {
+    pub enum ResticDiffMessage {
+        #[serde(rename = "change")]
+        DiffChange,
+        #[serde(rename = "statistics")]
+        DiffStatistics,
+    }
+}
+
+/// An item that differs between the two snapshots of `diff --json`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DiffChange {
+    /// Path of the item in the snapshots
+    pub path: String,
+    /// How the item changed: "+" added, "-" removed, or any of "T" (type), "M" (content), "U" (metadata) and
+    /// "?" (bitrot) when modified
+    pub modifier: String,
+}
+
+impl DiffChange {
+    pub fn is_added(&self) -> bool {
+        self.modifier == "+"
+    }
+
+    pub fn is_removed(&self) -> bool {
+        self.modifier == "-"
+    }
+
+    pub fn is_modified(&self) -> bool {
+        !self.is_added() && !self.is_removed()
+    }
+}
+
+/// The summary printed at the end of `diff --json`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct DiffStatistics {
+    /// The first snapshot, as given to restic
+    pub source_snapshot: String,
+    /// The second snapshot, as given to restic
+    pub target_snapshot: String,
+    /// Number of files in both snapshots that were modified
+    #[serde(default)]
+    pub changed_files: u64,
+    /// What is only in the second snapshot
+    #[serde(default)]
+    pub added: DiffStat,
+    /// What is only in the first snapshot
+    #[serde(default)]
+    pub removed: DiffStat,
+}
+
+/// Counts of the items and data only in one of the snapshots of a diff
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct DiffStat {
+    #[serde(default)]
+    pub files: u64,
+    #[serde(default)]
+    pub dirs: u64,
+    /// Number of items that are neither files nor directories, e.g. symlinks
+    #[serde(default)]
+    pub others: u64,
+    #[serde(default)]
+    pub data_blobs: u64,
+    #[serde(default)]
+    pub tree_blobs: u64,
+    /// Size in bytes of the data blobs
+    #[serde(default)]
+    pub bytes: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::ResticMessage;
+
+    #[test]
+    fn can_parse_change() {
+        let json = r#"{"message_type":"change","path":"/home/user/notes.txt","modifier":"M"}"#;
+
+        let message = ResticDiffMessage::parse_message(json).unwrap();
+
+        let ResticDiffMessage::DiffChange(change) = message else {
+            panic!("expected a change, got {message:?}");
+        };
+        assert_eq!(change.path, "/home/user/notes.txt");
+        assert!(change.is_modified());
+        assert!(!change.is_added());
+    }
+
+    #[test]
+    fn can_parse_statistics() {
+        let json = r#"{
+            "message_type": "statistics",
+            "source_snapshot": "1234abcd",
+            "target_snapshot": "5678ef90",
+            "changed_files": 3,
+            "added": {"files": 2, "dirs": 1, "others": 0, "data_blobs": 4, "tree_blobs": 2, "bytes": 8192},
+            "removed": {"files": 120, "dirs": 7, "others": 1, "data_blobs": 150, "tree_blobs": 8, "bytes": 1048576}
+        }"#;
+
+        let message = ResticDiffMessage::parse_message(json).unwrap();
+
+        let ResticDiffMessage::DiffStatistics(statistics) = message else {
+            panic!("expected statistics, got {message:?}");
+        };
+        assert_eq!(statistics.source_snapshot, "1234abcd");
+        assert_eq!(statistics.changed_files, 3);
+        assert_eq!(statistics.added.bytes, 8192);
+        assert_eq!(statistics.removed.files, 120);
+        assert_eq!(statistics.removed.others, 1);
+    }
+}
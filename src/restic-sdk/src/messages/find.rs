@@ -0,0 +1,35 @@
+use crate::messages::Node;
+use crate::messages::snapshot::null_as_default;
+use serde::Deserialize;
+
+/// The items of one snapshot matching the patterns of `find --json`
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct FindSnapshotMatches {
+    /// ID of the snapshot
+    pub snapshot: String,
+    /// Number of matching items
+    #[serde(default)]
+    pub hits: u64,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub matches: Vec<Node>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::NodeType;
+
+    #[test]
+    fn can_parse() {
+        let json = r#"[{"matches":[{"path":"/home/user/notes.txt","permissions":"-rw-r--r--","type":"file","mode":420,"mtime":"2025-03-30T10:00:00+02:00","uid":1000,"gid":1000,"size":1234}], "hits": 1, "snapshot": "1234567890abcdef"},{"matches":[{"path":"/home/user/old/notes.txt","type":"file","size":12},{"path":"/home/user/notes","type":"dir"}], "hits": 2, "snapshot": "fedcba0987654321"}]"#;
+
+        let results: Vec<FindSnapshotMatches> = serde_json::from_str(json).unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].snapshot, "1234567890abcdef");
+        assert_eq!(results[0].matches[0].path, "/home/user/notes.txt");
+        assert_eq!(results[0].matches[0].size, Some(1234));
+        assert_eq!(results[1].hits, 2);
+        assert_eq!(results[1].matches[1].node_type, NodeType::Dir);
+    }
+}
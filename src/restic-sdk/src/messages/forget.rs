@@ -0,0 +1,108 @@
+use crate::messages::Snapshot;
+use crate::messages::snapshot::null_as_default;
+use serde::{Deserialize, Serialize};
+
+/// The result of `forget` for one group of snapshots (see `--group-by`)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ForgetGroup {
+    /// Host of the group, empty when not grouped by host
+    #[serde(default)]
+    pub host: String,
+    /// Paths of the group, empty when not grouped by paths
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub paths: Vec<String>,
+    /// Tags of the group, empty when not grouped by tags
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub tags: Vec<String>,
+    /// Snapshots kept by the policy
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub keep: Vec<Snapshot>,
+    /// Snapshots removed by the policy
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub remove: Vec<Snapshot>,
+    /// Why each kept snapshot was kept
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub reasons: Vec<KeepReason>,
+}
+
+/// The rules of the policy that kept a snapshot
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct KeepReason {
+    /// The kept snapshot
+    pub snapshot: Snapshot,
+    /// The matching rules, e.g. "daily snapshot" or "last snapshot"
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub matches: Vec<String>,
+}
+
+impl ForgetGroup {
+    /// The rules that kept the snapshot, empty when it is not kept.
+    pub fn get_reasons(&self, snapshot_id: &str) -> &[String] {
+        self.reasons
+            .iter()
+            .find(|reason| reason.snapshot.id == snapshot_id)
+            .map(|reason| reason.matches.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse() {
+        let json = r#"[{
+            "tags": null,
+            "host": "myhost",
+            "paths": ["C:\\Users"],
+            "keep": [{
+                "time": "2025-07-06T18:00:00Z",
+                "paths": ["C:\\Users"],
+                "hostname": "myhost",
+                "id": "aaaa",
+                "short_id": "aa"
+            }],
+            "remove": [{
+                "time": "2025-07-05T18:00:00Z",
+                "paths": ["C:\\Users"],
+                "hostname": "myhost",
+                "id": "bbbb",
+                "short_id": "bb"
+            }],
+            "reasons": [{
+                "snapshot": {
+                    "time": "2025-07-06T18:00:00Z",
+                    "paths": ["C:\\Users"],
+                    "hostname": "myhost",
+                    "id": "aaaa",
+                    "short_id": "aa"
+                },
+                "matches": ["last snapshot", "daily snapshot"],
+                "counters": {"last": 0, "daily": 6}
+            }]
+        }]"#;
+
+        let groups: Vec<ForgetGroup> = serde_json::from_str(json).expect("parse should succeed");
+
+        let group = &groups[0];
+        assert_eq!(group.host, "myhost");
+        assert!(group.tags.is_empty());
+        assert_eq!(group.keep[0].id, "aaaa");
+        assert_eq!(group.remove[0].id, "bbbb");
+        assert_eq!(
+            group.get_reasons("aaaa"),
+            ["last snapshot", "daily snapshot"]
+        );
+        assert!(group.get_reasons("bbbb").is_empty());
+    }
+
+    #[test]
+    fn when_nothing_removed_then_remove_empty() {
+        let json = r#"[{"host": "", "paths": null, "tags": null, "keep": [], "remove": null, "reasons": []}]"#;
+
+        let groups: Vec<ForgetGroup> = serde_json::from_str(json).expect("parse should succeed");
+
+        assert!(groups[0].remove.is_empty());
+    }
+}
@@ -0,0 +1,101 @@
+use crate::messages::Snapshot;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A line printed by `ls --json`, the snapshot first, then one line per node
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "struct_type", rename_all = "snake_case")]
+pub enum LsMessage {
+    Snapshot(Snapshot),
+    Node(Node),
+}
+
+/// A file, directory or other item of a snapshot
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Node {
+    /// Name of the item, empty in the results of `find`
+    #[serde(default)]
+    pub name: String,
+    #[serde(rename = "type")]
+    pub node_type: NodeType,
+    /// Absolute path of the item in the snapshot
+    pub path: String,
+    /// Size in bytes, only set for files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// Permissions like "-rw-r--r--"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permissions: Option<String>,
+    /// Time the item was last modified
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mtime: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeType {
+    File,
+    Dir,
+    Symlink,
+    Dev,
+    Chardev,
+    Fifo,
+    Socket,
+    Irregular,
+    #[serde(other)]
+    Unknown,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_snapshot() {
+        let json = r#"{"time":"2025-03-31T22:00:00.123+02:00","tree":"abcd","paths":["/home/user"],"hostname":"laptop","username":"user","id":"1234567890abcdef","short_id":"12345678","struct_type":"snapshot","message_type":"snapshot"}"#;
+
+        let message: LsMessage = serde_json::from_str(json).unwrap();
+
+        let LsMessage::Snapshot(snapshot) = message else {
+            panic!("should be a snapshot");
+        };
+        assert_eq!(snapshot.id, "1234567890abcdef");
+        assert_eq!(snapshot.paths, vec!["/home/user"]);
+    }
+
+    #[test]
+    fn can_parse_nodes() {
+        let file = r#"{"name":"notes.txt","type":"file","path":"/home/user/notes.txt","uid":1000,"gid":1000,"size":1234,"mode":420,"permissions":"-rw-r--r--","mtime":"2025-03-30T10:00:00+02:00","atime":"2025-03-30T10:00:00+02:00","ctime":"2025-03-30T10:00:00+02:00","inode":42,"struct_type":"node","message_type":"node"}"#;
+        let dir = r#"{"name":"docs","type":"dir","path":"/home/user/docs","mode":2147484141,"struct_type":"node"}"#;
+
+        let file: LsMessage = serde_json::from_str(file).unwrap();
+        let dir: LsMessage = serde_json::from_str(dir).unwrap();
+
+        assert_eq!(
+            file,
+            LsMessage::Node(Node {
+                name: "notes.txt".to_owned(),
+                node_type: NodeType::File,
+                path: "/home/user/notes.txt".to_owned(),
+                size: Some(1234),
+                permissions: Some("-rw-r--r--".to_owned()),
+                mtime: Some("2025-03-30T08:00:00Z".parse().unwrap()),
+            })
+        );
+        let LsMessage::Node(dir) = dir else {
+            panic!("should be a node");
+        };
+        assert_eq!(dir.node_type, NodeType::Dir);
+        assert_eq!(dir.size, None);
+    }
+
+    #[test]
+    fn when_unknown_node_type_then_unknown() {
+        let json = r#"{"name":"x","type":"whiteout","path":"/x","struct_type":"node"}"#;
+
+        let LsMessage::Node(node) = serde_json::from_str(json).unwrap() else {
+            panic!("should be a node");
+        };
+        assert_eq!(node.node_type, NodeType::Unknown);
+    }
+}
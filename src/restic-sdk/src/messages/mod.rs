@@ -1,11 +1,27 @@
 mod restic_message;
 
 mod backup;
+mod diff;
 mod exit_error;
+mod find;
+mod forget;
 mod init;
+mod ls;
+mod prune;
+mod snapshot;
+mod stats;
+mod tag;
 mod version;
 
 pub use backup::*;
+pub use diff::*;
 pub use exit_error::*;
+pub use find::*;
+pub use forget::*;
 pub use init::*;
+pub use ls::*;
+pub use prune::*;
+pub use snapshot::*;
+pub use stats::*;
+pub use tag::*;
 pub use version::*;
@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+
+/// Statistics printed by `prune`, sizes in bytes.
+///
+/// Restic has no JSON output for prune, so these are parsed from its text output. Sizes are printed with 3 decimals
+/// (e.g. "1.531 KiB"), so they are approximate.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct PruneStats {
+    /// Blobs in packs that are repacked
+    pub blobs_to_repack: u64,
+    pub bytes_to_repack: u64,
+    /// Unused blobs removed by repacking
+    pub blobs_removed_by_repack: u64,
+    pub bytes_removed_by_repack: u64,
+    /// Unused blobs in packs that are deleted
+    pub blobs_to_delete: u64,
+    pub bytes_to_delete: u64,
+    /// All blobs removed, the space freed
+    pub blobs_pruned: u64,
+    pub bytes_pruned: u64,
+    /// Blobs left in the repository
+    pub blobs_remaining: u64,
+    pub bytes_remaining: u64,
+    /// Unused data left in the repository
+    pub unused_bytes_after_prune: u64,
+    /// Pack files kept, repacked and deleted, only printed in verbose mode
+    pub packs_to_keep: Option<u64>,
+    pub packs_to_repack: Option<u64>,
+    pub packs_to_delete: Option<u64>,
+}
+
+impl PruneStats {
+    /// Parses the statistics from the lines restic printed, `None` when there are none (e.g. prune failed early).
+    pub fn parse<'a>(lines: impl IntoIterator<Item = &'a str>) -> Option<Self> {
+        let mut stats = PruneStats::default();
+        let mut found = false;
+
+        for line in lines {
+            let Some((label, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+
+            if let Some(count) = value.strip_suffix("packs").and_then(parse_count) {
+                let packs = match label.trim() {
+                    "to keep" => &mut stats.packs_to_keep,
+                    "to repack" => &mut stats.packs_to_repack,
+                    "to delete" => &mut stats.packs_to_delete,
+                    _ => continue,
+                };
+                *packs = Some(count);
+                found = true;
+                continue;
+            }
+
+            if label.trim() == "unused size after prune" {
+                // e.g. "0 B (0.00% of remaining size)"
+                let size = value.split_once('(').map_or(value, |(size, _)| size);
+                if let Some(bytes) = parse_bytes(size) {
+                    stats.unused_bytes_after_prune = bytes;
+                    found = true;
+                }
+                continue;
+            }
+
+            let Some((blobs, bytes)) = parse_blobs_and_bytes(value) else {
+                continue;
+            };
+            let (blobs_field, bytes_field) = match label.trim() {
+                "to repack" => (&mut stats.blobs_to_repack, &mut stats.bytes_to_repack),
+                "this removes" => (
+                    &mut stats.blobs_removed_by_repack,
+                    &mut stats.bytes_removed_by_repack,
+                ),
+                "to delete" => (&mut stats.blobs_to_delete, &mut stats.bytes_to_delete),
+                "total prune" => (&mut stats.blobs_pruned, &mut stats.bytes_pruned),
+                "remaining" => (&mut stats.blobs_remaining, &mut stats.bytes_remaining),
+                _ => continue,
+            };
+            *blobs_field = blobs;
+            *bytes_field = bytes;
+            found = true;
+        }
+
+        found.then_some(stats)
+    }
+}
+
+/// Parses "69 blobs / 1.078 MiB".
+fn parse_blobs_and_bytes(value: &str) -> Option<(u64, u64)> {
+    let (blobs, size) = value.split_once('/')?;
+    let blobs = parse_count(blobs.trim().strip_suffix("blobs")?)?;
+    Some((blobs, parse_bytes(size)?))
+}
+
+fn parse_count(value: &str) -> Option<u64> {
+    value.trim().parse().ok()
+}
+
+/// Parses a size as printed by restic, e.g. "1.531 KiB".
+pub(crate) fn parse_bytes(size: &str) -> Option<u64> {
+    let (number, unit) = size.trim().split_once(' ')?;
+    let number: f64 = number.parse().ok()?;
+    let exponent = match unit.trim() {
+        "B" => 0,
+        "KiB" => 1,
+        "MiB" => 2,
+        "GiB" => 3,
+        "TiB" => 4,
+        _ => return None,
+    };
+    Some((number * 1024f64.powi(exponent)).round() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse() {
+        let output = "loading indexes...
+loading all snapshots...
+finding data that is still in use for 1 snapshots
+searching used packs...
+collecting packs for deletion and repacking
+
+to repack:            69 blobs / 1.078 MiB
+this removes:         67 blobs / 1.047 MiB
+to delete:             7 blobs / 25.726 KiB
+total prune:          74 blobs / 1.072 MiB
+remaining:            16 blobs / 38.003 KiB
+unused size after prune: 1 B (0.00% of remaining size)
+
+totally used packs:             5
+partly used packs:              1
+unused packs:                   1
+
+to keep:           5 packs
+to repack:         1 packs
+to delete:         1 packs
+rebuilding index
+done";
+
+        let stats = PruneStats::parse(output.lines()).expect("should find stats");
+
+        assert_eq!(stats.blobs_to_repack, 69);
+        assert_eq!(stats.bytes_to_repack, 1_130_365);
+        assert_eq!(stats.blobs_removed_by_repack, 67);
+        assert_eq!(stats.blobs_to_delete, 7);
+        assert_eq!(stats.bytes_to_delete, 26_343);
+        assert_eq!(stats.blobs_pruned, 74);
+        assert_eq!(stats.blobs_remaining, 16);
+        assert_eq!(stats.unused_bytes_after_prune, 1);
+        assert_eq!(stats.packs_to_keep, Some(5));
+        assert_eq!(stats.packs_to_repack, Some(1));
+        assert_eq!(stats.packs_to_delete, Some(1));
+    }
+
+    #[test]
+    fn when_no_stats_then_none() {
+        let output = "loading indexes...\nFatal: unable to open repository";
+
+        assert_eq!(PruneStats::parse(output.lines()), None);
+    }
+
+    #[test]
+    fn can_parse_bytes() {
+        assert_eq!(parse_bytes("0 B"), Some(0));
+        assert_eq!(parse_bytes("1.500 KiB"), Some(1536));
+        assert_eq!(parse_bytes("2.000 GiB"), Some(2 * 1024 * 1024 * 1024));
+        assert_eq!(parse_bytes("12 blobs"), None);
+    }
+}
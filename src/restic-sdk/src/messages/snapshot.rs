@@ -0,0 +1,85 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A snapshot as listed by restic (e.g. by `snapshots` or `forget`)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    /// ID of the snapshot
+    pub id: String,
+    /// Abbreviated ID of the snapshot
+    #[serde(default)]
+    pub short_id: String,
+    /// Time the snapshot was taken
+    pub time: DateTime<Utc>,
+    /// ID of the parent snapshot, if any
+    #[serde(default)]
+    pub parent: Option<String>,
+    /// Paths that were backed up
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// Hostname of the machine that was backed up
+    #[serde(default)]
+    pub hostname: String,
+    /// User that made the backup
+    #[serde(default)]
+    pub username: String,
+    /// Tags of the snapshot, missing when there are none
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub tags: Vec<String>,
+}
+
+/// Restic writes `null` for empty lists in some places.
+pub(crate) fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse() {
+        let json = r#"{
+            "time": "2025-07-06T18:56:28.8860478-05:00",
+            "parent": "2f8bb1c9e0aa3b2b6c9a7f7c3a9c3c4e4a5b6c7d8e9f0a1b2c3d4e5f6a7b8c9d",
+            "tree": "b6a8c8d7e8f9a0b1c2d3e4f5a6b7c8d9e0f1a2b3c4d5e6f7a8b9c0d1e2f3a4b5",
+            "paths": ["C:\\Users"],
+            "hostname": "myhost",
+            "username": "myuser",
+            "tags": ["job:daily"],
+            "program_version": "restic 0.18.0",
+            "id": "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef",
+            "short_id": "01234567"
+        }"#;
+
+        let snapshot: Snapshot = serde_json::from_str(json).expect("parse should succeed");
+
+        assert_eq!(snapshot.short_id, "01234567");
+        assert_eq!(snapshot.paths, vec!["C:\\Users"]);
+        assert_eq!(snapshot.tags, vec!["job:daily"]);
+        assert_eq!(
+            snapshot.time.to_rfc3339(),
+            "2025-07-06T23:56:28.886047800+00:00"
+        );
+    }
+
+    #[test]
+    fn when_tags_null_then_empty() {
+        let json = r#"{
+            "time": "2025-07-06T18:56:28Z",
+            "paths": ["/home"],
+            "hostname": "myhost",
+            "tags": null,
+            "id": "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef"
+        }"#;
+
+        let snapshot: Snapshot = serde_json::from_str(json).expect("parse should succeed");
+
+        assert!(snapshot.tags.is_empty());
+        assert_eq!(snapshot.parent, None);
+    }
+}
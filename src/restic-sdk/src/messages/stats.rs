@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+
+/// The statistics printed by `stats --json`, which fields are set depends on the mode
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct RepositoryStats {
+    /// Size in bytes, of the restored files or of the stored data for `raw-data`
+    #[serde(default)]
+    pub total_size: u64,
+    /// Number of files, not set for `raw-data`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_file_count: Option<u64>,
+    /// Number of blobs, only set for `raw-data` and `blobs-per-file`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_blob_count: Option<u64>,
+    /// Number of snapshots the statistics are computed for
+    #[serde(default)]
+    pub snapshots_count: u64,
+    /// Size in bytes of the stored data before compression, only set for `raw-data`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_uncompressed_size: Option<u64>,
+    /// Uncompressed size divided by the stored size, only set for `raw-data`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression_ratio: Option<f64>,
+    /// Percent of the data that is compressed, only set for `raw-data`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression_progress: Option<f64>,
+    /// Percent of the space saved by compression, only set for `raw-data`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression_space_saving: Option<f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_restore_size() {
+        let json = r#"{"total_size":5368709120,"total_file_count":1250,"snapshots_count":3}"#;
+
+        let stats: RepositoryStats = serde_json::from_str(json).unwrap();
+
+        assert_eq!(
+            stats,
+            RepositoryStats {
+                total_size: 5_368_709_120,
+                total_file_count: Some(1250),
+                snapshots_count: 3,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn can_parse_raw_data() {
+        let json = r#"{
+            "total_size": 1048576,
+            "total_uncompressed_size": 2097152,
+            "compression_ratio": 2,
+            "compression_progress": 100,
+            "compression_space_saving": 50,
+            "total_blob_count": 42,
+            "snapshots_count": 3
+        }"#;
+
+        let stats: RepositoryStats = serde_json::from_str(json).unwrap();
+
+        assert_eq!(stats.total_size, 1_048_576);
+        assert_eq!(stats.total_uncompressed_size, Some(2_097_152));
+        assert_eq!(stats.compression_ratio, Some(2.0));
+        assert_eq!(stats.compression_space_saving, Some(50.0));
+        assert_eq!(stats.total_blob_count, Some(42));
+        assert_eq!(stats.total_file_count, None);
+    }
+}
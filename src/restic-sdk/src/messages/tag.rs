@@ -0,0 +1,63 @@
+use crate::messages::ExitError;
+use crate::restic_message;
+use serde::Deserialize;
+
+restic_message! {
+    pub enum ResticTagMessage {
+        #[serde(rename = "changed")]
+        TagChangedSnapshot,
+        #[serde(rename = "summary")]
+        TagSummary,
+    }
+}
+
+/// A snapshot whose tags changed, restic replaces it with a new snapshot
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct TagChangedSnapshot {
+    /// ID of the replaced snapshot
+    pub old_snapshot_id: String,
+    /// ID of the snapshot with the new tags
+    pub new_snapshot_id: String,
+}
+
+/// The summary printed at the end of `tag --json`
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct TagSummary {
+    /// Number of snapshots whose tags changed
+    pub changed_snapshots: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::ResticMessage;
+
+    #[test]
+    fn can_parse_changed_snapshot() {
+        let json = r#"{"message_type":"changed","old_snapshot_id":"1234abcd","new_snapshot_id":"5678ef90"}"#;
+
+        let message = ResticTagMessage::parse_message(json).unwrap();
+
+        assert_eq!(
+            message,
+            ResticTagMessage::TagChangedSnapshot(TagChangedSnapshot {
+                old_snapshot_id: "1234abcd".to_owned(),
+                new_snapshot_id: "5678ef90".to_owned(),
+            })
+        );
+    }
+
+    #[test]
+    fn can_parse_summary() {
+        let json = r#"{"message_type":"summary","changed_snapshots":2}"#;
+
+        let message = ResticTagMessage::parse_message(json).unwrap();
+
+        assert_eq!(
+            message,
+            ResticTagMessage::TagSummary(TagSummary {
+                changed_snapshots: 2
+            })
+        );
+    }
+}
@@ -0,0 +1,429 @@
+//! Typed retention policy and prune limits, parsed up front so a malformed value fails when it is loaded instead of
+//! when restic runs.
+
+use crate::BuilderValue;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use thiserror::Error;
+
+mod simulation;
+
+pub use simulation::*;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum RetentionError {
+    #[error("invalid duration '{0}', expected e.g. \"1y5m7d2h\"")]
+    InvalidDuration(String),
+    #[error("invalid size '{0}', expected e.g. \"500M\" or \"10G\"")]
+    InvalidSize(String),
+    #[error("invalid max unused '{0}', expected e.g. \"5%\", \"10G\" or \"unlimited\"")]
+    InvalidMaxUnused(String),
+    #[error("the policy keeps no snapshots, set unsafe_allow_remove_all to remove all snapshots")]
+    EmptyPolicy,
+}
+
+/// The snapshots `forget` keeps, snapshots matching any rule are kept.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RetentionPolicy {
+    pub keep_last: Option<u32>,
+    pub keep_hourly: Option<u32>,
+    pub keep_daily: Option<u32>,
+    pub keep_weekly: Option<u32>,
+    pub keep_monthly: Option<u32>,
+    pub keep_yearly: Option<u32>,
+    pub keep_within: Option<RetentionDuration>,
+    pub keep_within_hourly: Option<RetentionDuration>,
+    pub keep_within_daily: Option<RetentionDuration>,
+    pub keep_within_weekly: Option<RetentionDuration>,
+    pub keep_within_monthly: Option<RetentionDuration>,
+    pub keep_within_yearly: Option<RetentionDuration>,
+    /// Tag lists separated by comma, snapshots with all tags of any list are kept.
+    pub keep_tags: Vec<String>,
+    /// Allows a policy without rules, which removes every snapshot.
+    pub unsafe_allow_remove_all: bool,
+}
+
+impl RetentionPolicy {
+    /// The policy has no rule that keeps a snapshot, zero counts and durations keep nothing.
+    pub fn is_empty(&self) -> bool {
+        self.counts().all(|(_, count)| count == 0)
+            && self.durations().all(|(_, duration)| duration.is_zero())
+            && self.tag_lists().next().is_none()
+    }
+
+    /// Rejects an empty policy, unless `unsafe_allow_remove_all` is set.
+    pub fn validate(&self) -> Result<(), RetentionError> {
+        if self.is_empty() && !self.unsafe_allow_remove_all {
+            return Err(RetentionError::EmptyPolicy);
+        }
+        Ok(())
+    }
+
+    /// The tag lists that keep snapshots, without empty ones.
+    pub(crate) fn tag_lists(&self) -> impl Iterator<Item = &str> {
+        self.keep_tags
+            .iter()
+            .map(String::as_str)
+            .filter(|tags| !tags.is_empty())
+    }
+
+    fn counts(&self) -> impl Iterator<Item = (&'static str, u32)> {
+        [
+            ("last", self.keep_last),
+            ("hourly", self.keep_hourly),
+            ("daily", self.keep_daily),
+            ("weekly", self.keep_weekly),
+            ("monthly", self.keep_monthly),
+            ("yearly", self.keep_yearly),
+        ]
+        .into_iter()
+        .filter_map(|(name, count)| count.map(|count| (name, count)))
+    }
+
+    fn durations(&self) -> impl Iterator<Item = (&'static str, &RetentionDuration)> {
+        [
+            ("within", &self.keep_within),
+            ("within hourly", &self.keep_within_hourly),
+            ("within daily", &self.keep_within_daily),
+            ("within weekly", &self.keep_within_weekly),
+            ("within monthly", &self.keep_within_monthly),
+            ("within yearly", &self.keep_within_yearly),
+        ]
+        .into_iter()
+        .filter_map(|(name, duration)| duration.as_ref().map(|duration| (name, duration)))
+    }
+}
+
+/// A summary of the rules, e.g. "keep last 3, daily 7, within 1y".
+impl Display for RetentionPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return match self.unsafe_allow_remove_all {
+                true => write!(f, "remove all"),
+                false => write!(f, "keep nothing"),
+            };
+        }
+
+        let rules = self
+            .counts()
+            .filter(|(_, count)| *count > 0)
+            .map(|(name, count)| format!("{name} {count}"))
+            .chain(
+                self.durations()
+                    .filter(|(_, duration)| !duration.is_zero())
+                    .map(|(name, duration)| format!("{name} {duration}")),
+            )
+            .chain(self.tag_lists().map(|tags| format!("tag '{tags}'")))
+            .collect::<Vec<_>>();
+        write!(f, "keep {}", rules.join(", "))
+    }
+}
+
+/// A duration as restic accepts it for `--keep-within`, e.g. "1y5m7d2h".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RetentionDuration {
+    pub years: u32,
+    pub months: u32,
+    pub days: u32,
+    pub hours: u32,
+}
+
+impl RetentionDuration {
+    pub fn is_zero(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl FromStr for RetentionDuration {
+    type Err = RetentionError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || RetentionError::InvalidDuration(value.to_owned());
+        let mut duration = RetentionDuration::default();
+        let mut seen = String::new();
+        let mut rest = value.trim();
+        if rest.is_empty() {
+            return Err(invalid());
+        }
+
+        while !rest.is_empty() {
+            let digits = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .ok_or_else(invalid)?;
+            let number = rest[..digits].parse().map_err(|_| invalid())?;
+            let unit = rest[digits..].chars().next().ok_or_else(invalid)?;
+            if seen.contains(unit) {
+                return Err(invalid());
+            }
+
+            let field = match unit {
+                'y' => &mut duration.years,
+                'm' => &mut duration.months,
+                'd' => &mut duration.days,
+                'h' => &mut duration.hours,
+                _ => return Err(invalid()),
+            };
+            *field = number;
+            seen.push(unit);
+            rest = &rest[digits + unit.len_utf8()..];
+        }
+
+        Ok(duration)
+    }
+}
+
+impl Display for RetentionDuration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0h");
+        }
+        for (value, unit) in [
+            (self.years, 'y'),
+            (self.months, 'm'),
+            (self.days, 'd'),
+            (self.hours, 'h'),
+        ] {
+            if value > 0 {
+                write!(f, "{value}{unit}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl BuilderValue for RetentionDuration {
+    fn to_builder_value(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// A size in bytes as restic accepts it, e.g. "500M" or "10G", without a unit in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ByteSize(pub u64);
+
+const SIZE_UNITS: [(char, u32); 4] = [('T', 4), ('G', 3), ('M', 2), ('K', 1)];
+
+impl FromStr for ByteSize {
+    type Err = RetentionError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || RetentionError::InvalidSize(value.to_owned());
+        let trimmed = value.trim();
+        let last = trimmed.chars().last().ok_or_else(invalid)?;
+
+        let (number, exponent) = match last.to_ascii_uppercase() {
+            'B' => (&trimmed[..trimmed.len() - 1], 0),
+            c if c.is_ascii_digit() => (trimmed, 0),
+            c => {
+                let (_, exponent) = SIZE_UNITS
+                    .iter()
+                    .find(|(unit, _)| *unit == c)
+                    .ok_or_else(invalid)?;
+                (&trimmed[..trimmed.len() - 1], *exponent)
+            }
+        };
+
+        let number: u64 = number.parse().map_err(|_| invalid())?;
+        number
+            .checked_mul(1024u64.pow(exponent))
+            .map(ByteSize)
+            .ok_or_else(invalid)
+    }
+}
+
+/// Uses the largest unit that represents the size exactly.
+impl Display for ByteSize {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (unit, exponent) in SIZE_UNITS {
+            let factor = 1024u64.pow(exponent);
+            if self.0 > 0 && self.0.is_multiple_of(factor) {
+                return write!(f, "{}{unit}", self.0 / factor);
+            }
+        }
+        write!(f, "{}", self.0)
+    }
+}
+
+impl BuilderValue for ByteSize {
+    fn to_builder_value(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// How much unused data prune tolerates, restic's `--max-unused`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MaxUnused {
+    Unlimited,
+    /// Percent of the repository size.
+    Percent(f64),
+    Size(ByteSize),
+}
+
+impl FromStr for MaxUnused {
+    type Err = RetentionError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || RetentionError::InvalidMaxUnused(value.to_owned());
+        let trimmed = value.trim();
+
+        if trimmed == "unlimited" {
+            return Ok(MaxUnused::Unlimited);
+        }
+        if let Some(percent) = trimmed.strip_suffix('%') {
+            let percent: f64 = percent.trim().parse().map_err(|_| invalid())?;
+            if !percent.is_finite() || !(0.0..=100.0).contains(&percent) {
+                return Err(invalid());
+            }
+            return Ok(MaxUnused::Percent(percent));
+        }
+        trimmed.parse().map(MaxUnused::Size).map_err(|_| invalid())
+    }
+}
+
+impl Display for MaxUnused {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MaxUnused::Unlimited => write!(f, "unlimited"),
+            MaxUnused::Percent(percent) => write!(f, "{percent}%"),
+            MaxUnused::Size(size) => write!(f, "{size}"),
+        }
+    }
+}
+
+impl BuilderValue for MaxUnused {
+    fn to_builder_value(&self) -> String {
+        self.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_duration() {
+        let duration: RetentionDuration = "1y5m7d2h".parse().unwrap();
+
+        assert_eq!(
+            duration,
+            RetentionDuration {
+                years: 1,
+                months: 5,
+                days: 7,
+                hours: 2
+            }
+        );
+        assert_eq!(duration.to_string(), "1y5m7d2h");
+    }
+
+    #[test]
+    fn when_duration_in_any_order_then_displayed_in_order() {
+        let duration: RetentionDuration = "12h30d".parse().unwrap();
+
+        assert_eq!(duration.to_string(), "30d12h");
+    }
+
+    #[test]
+    fn when_invalid_duration_then_error() {
+        for value in ["", "30", "d", "30x", "1y1y", "-1d", "1.5d", "30 d"] {
+            assert_eq!(
+                value.parse::<RetentionDuration>(),
+                Err(RetentionError::InvalidDuration(value.to_owned())),
+                "{value}"
+            );
+        }
+    }
+
+    #[test]
+    fn can_parse_size() {
+        assert_eq!("1024".parse(), Ok(ByteSize(1024)));
+        assert_eq!("100b".parse(), Ok(ByteSize(100)));
+        assert_eq!("500M".parse(), Ok(ByteSize(500 * 1024 * 1024)));
+        assert_eq!("10g".parse(), Ok(ByteSize(10 * 1024 * 1024 * 1024)));
+        assert_eq!("2T".parse(), Ok(ByteSize(2 * 1024u64.pow(4))));
+    }
+
+    #[test]
+    fn size_is_displayed_with_largest_exact_unit() {
+        assert_eq!(ByteSize(0).to_string(), "0");
+        assert_eq!(ByteSize(1000).to_string(), "1000");
+        assert_eq!(ByteSize(1536).to_string(), "1536");
+        assert_eq!(ByteSize(2048).to_string(), "2K");
+        assert_eq!(ByteSize(10 * 1024 * 1024 * 1024).to_string(), "10G");
+    }
+
+    #[test]
+    fn when_invalid_size_then_error() {
+        for value in ["", "G", "10X", "10MB", "ten", "1.5G", "-1M", "99999999999T"] {
+            assert_eq!(
+                value.parse::<ByteSize>(),
+                Err(RetentionError::InvalidSize(value.to_owned())),
+                "{value}"
+            );
+        }
+    }
+
+    #[test]
+    fn can_parse_max_unused() {
+        assert_eq!("5%".parse(), Ok(MaxUnused::Percent(5.0)));
+        assert_eq!("2.5%".parse(), Ok(MaxUnused::Percent(2.5)));
+        assert_eq!(
+            "10G".parse(),
+            Ok(MaxUnused::Size(ByteSize(10 * 1024u64.pow(3))))
+        );
+        assert_eq!("unlimited".parse(), Ok(MaxUnused::Unlimited));
+        assert_eq!(MaxUnused::Percent(2.5).to_string(), "2.5%");
+    }
+
+    #[test]
+    fn when_invalid_max_unused_then_error() {
+        for value in ["", "%", "101%", "-1%", "five%", "10X"] {
+            assert_eq!(
+                value.parse::<MaxUnused>(),
+                Err(RetentionError::InvalidMaxUnused(value.to_owned())),
+                "{value}"
+            );
+        }
+    }
+
+    #[test]
+    fn when_empty_policy_then_invalid() {
+        let policy = RetentionPolicy {
+            keep_last: Some(0),
+            keep_within: Some(RetentionDuration::default()),
+            ..Default::default()
+        };
+
+        assert!(policy.is_empty());
+        assert_eq!(policy.validate(), Err(RetentionError::EmptyPolicy));
+    }
+
+    #[test]
+    fn when_empty_policy_allows_remove_all_then_valid() {
+        let policy = RetentionPolicy {
+            unsafe_allow_remove_all: true,
+            ..Default::default()
+        };
+
+        assert_eq!(policy.validate(), Ok(()));
+        assert_eq!(policy.to_string(), "remove all");
+    }
+
+    #[test]
+    fn can_display_policy() {
+        let policy = RetentionPolicy {
+            keep_last: Some(3),
+            keep_daily: Some(7),
+            keep_weekly: Some(0),
+            keep_within: Some("1y".parse().unwrap()),
+            keep_tags: vec!["pinned".to_owned(), "manual,yearly".to_owned()],
+            ..Default::default()
+        };
+
+        assert_eq!(policy.validate(), Ok(()));
+        assert_eq!(
+            policy.to_string(),
+            "keep last 3, daily 7, within 1y, tag 'pinned', tag 'manual,yearly'"
+        );
+    }
+}
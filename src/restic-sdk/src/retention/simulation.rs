@@ -0,0 +1,635 @@
+use crate::messages::{KeepReason, Snapshot};
+use crate::retention::{RetentionDuration, RetentionPolicy};
+use chrono::{
+    DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, Offset, TimeDelta, TimeZone, Timelike,
+};
+use std::cmp::Reverse;
+
+/// The snapshots a policy keeps and removes, newest first, as `forget` would compute them for one group.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RetentionSimulation {
+    pub keep: Vec<Snapshot>,
+    pub remove: Vec<Snapshot>,
+    /// Why each kept snapshot was kept, with the same wording as restic
+    pub reasons: Vec<KeepReason>,
+}
+
+impl RetentionSimulation {
+    /// The time between the oldest and the newest kept snapshot, `None` when nothing is kept.
+    pub fn covered(&self) -> Option<TimeDelta> {
+        let newest = self.keep.first()?;
+        let oldest = self.keep.last()?;
+        Some(newest.time - oldest.time)
+    }
+}
+
+/// Maps the time of a snapshot (or its position for `last`) to the bucket it falls into.
+type Bucker<Tz> = fn(&DateTime<Tz>, usize) -> i64;
+
+struct Bucket<Tz: TimeZone> {
+    count: u32,
+    bucker: Bucker<Tz>,
+    last: Option<i64>,
+    reason: String,
+}
+
+struct BucketWithin<Tz: TimeZone> {
+    within: RetentionDuration,
+    bucker: Bucker<Tz>,
+    last: Option<i64>,
+    reason: String,
+}
+
+impl RetentionPolicy {
+    /// Computes which snapshots of one group the policy keeps, without touching the repository.
+    ///
+    /// Follows restic's `forget`: snapshots are walked newest first, the `keep_*` counts keep the newest snapshot of
+    /// each hour, day, ISO week, month or year (in `timezone`) until the count is used up, and the oldest snapshot
+    /// when a count is left. The `keep_within*` durations are relative to the newest snapshot, not to now. An empty
+    /// policy keeps everything, as restic refuses to run it, unless `unsafe_allow_remove_all` is set.
+    pub fn simulate<Tz: TimeZone>(
+        &self,
+        snapshots: &[Snapshot],
+        timezone: &Tz,
+    ) -> RetentionSimulation {
+        let mut list = snapshots.to_vec();
+        list.sort_by_key(|snapshot| Reverse(snapshot.time));
+
+        if self.is_empty() {
+            return match self.unsafe_allow_remove_all {
+                true => RetentionSimulation {
+                    remove: list,
+                    ..Default::default()
+                },
+                false => RetentionSimulation {
+                    reasons: list
+                        .iter()
+                        .map(|snapshot| KeepReason {
+                            snapshot: snapshot.clone(),
+                            matches: vec!["policy is empty".to_owned()],
+                        })
+                        .collect(),
+                    keep: list,
+                    ..Default::default()
+                },
+            };
+        }
+
+        let mut buckets: Vec<Bucket<Tz>> = [
+            (self.keep_last, always as Bucker<Tz>, "last snapshot"),
+            (self.keep_hourly, hour, "hourly snapshot"),
+            (self.keep_daily, day, "daily snapshot"),
+            (self.keep_weekly, week, "weekly snapshot"),
+            (self.keep_monthly, month, "monthly snapshot"),
+            (self.keep_yearly, year, "yearly snapshot"),
+        ]
+        .into_iter()
+        .filter_map(|(count, bucker, reason)| {
+            Some(Bucket {
+                count: count.filter(|count| *count > 0)?,
+                bucker,
+                last: None,
+                reason: reason.to_owned(),
+            })
+        })
+        .collect();
+
+        let mut buckets_within: Vec<BucketWithin<Tz>> = [
+            (self.keep_within_hourly, hour as Bucker<Tz>, "hourly"),
+            (self.keep_within_daily, day, "daily"),
+            (self.keep_within_weekly, week, "weekly"),
+            (self.keep_within_monthly, month, "monthly"),
+            (self.keep_within_yearly, year, "yearly"),
+        ]
+        .into_iter()
+        .filter_map(|(within, bucker, name)| {
+            let within = within.filter(|within| !within.is_zero())?;
+            Some(BucketWithin {
+                within,
+                bucker,
+                last: None,
+                reason: format!("{name} within {within}"),
+            })
+        })
+        .collect();
+
+        let keep_tags: Vec<Vec<&str>> = self
+            .tag_lists()
+            .map(|tags| tags.split(',').collect())
+            .collect();
+
+        let mut result = RetentionSimulation::default();
+        let Some(latest) = list
+            .first()
+            .map(|snapshot| snapshot.time.with_timezone(timezone))
+        else {
+            return result;
+        };
+        let oldest_nr = list.len() - 1;
+
+        for (nr, snapshot) in list.into_iter().enumerate() {
+            let time = snapshot.time.with_timezone(timezone);
+            let mut matches = Vec::new();
+
+            // Tags are not counted, every snapshot with all the tags of a list is kept.
+            for tags in &keep_tags {
+                if tags
+                    .iter()
+                    .all(|tag| snapshot.tags.iter().any(|t| t == tag))
+                {
+                    matches.push(format!("has tags [{}]", tags.join(", ")));
+                }
+            }
+
+            if let Some(within) = self.keep_within.filter(|within| !within.is_zero())
+                && time > subtract(&latest, &within)
+            {
+                matches.push(format!("within {within}"));
+            }
+
+            for bucket in buckets.iter_mut().filter(|bucket| bucket.count > 0) {
+                let value = (bucket.bucker)(&time, nr);
+                // The oldest snapshot is also kept while some counts are left, to keep the longest history.
+                if bucket.last != Some(value) || nr == oldest_nr {
+                    bucket.last = Some(value);
+                    bucket.count -= 1;
+                    matches.push(bucket.reason.clone());
+                }
+            }
+
+            for bucket in &mut buckets_within {
+                if time > subtract(&latest, &bucket.within) {
+                    let value = (bucket.bucker)(&time, nr);
+                    if bucket.last != Some(value) || nr == oldest_nr {
+                        bucket.last = Some(value);
+                        matches.push(bucket.reason.clone());
+                    }
+                }
+            }
+
+            if matches.is_empty() {
+                result.remove.push(snapshot);
+            } else {
+                result.reasons.push(KeepReason {
+                    snapshot: snapshot.clone(),
+                    matches,
+                });
+                result.keep.push(snapshot);
+            }
+        }
+
+        result
+    }
+}
+
+fn always<Tz: TimeZone>(_: &DateTime<Tz>, nr: usize) -> i64 {
+    nr as i64
+}
+
+fn hour<Tz: TimeZone>(time: &DateTime<Tz>, _: usize) -> i64 {
+    day(time, 0) * 100 + time.hour() as i64
+}
+
+fn day<Tz: TimeZone>(time: &DateTime<Tz>, _: usize) -> i64 {
+    month(time, 0) * 100 + time.day() as i64
+}
+
+fn week<Tz: TimeZone>(time: &DateTime<Tz>, _: usize) -> i64 {
+    let week = time.iso_week();
+    week.year() as i64 * 100 + week.week() as i64
+}
+
+fn month<Tz: TimeZone>(time: &DateTime<Tz>, _: usize) -> i64 {
+    year(time, 0) * 100 + time.month() as i64
+}
+
+fn year<Tz: TimeZone>(time: &DateTime<Tz>, _: usize) -> i64 {
+    time.year() as i64
+}
+
+/// Subtracts the duration like restic (Go's `AddDate`), days past the end of a month overflow into the next month,
+/// e.g. March 31st minus one month is March 3rd (February 31st).
+fn subtract<Tz: TimeZone>(time: &DateTime<Tz>, duration: &RetentionDuration) -> DateTime<Tz> {
+    let local = time.naive_local();
+    let months = local.year() as i64 * 12 + local.month0() as i64
+        - duration.years as i64 * 12
+        - duration.months as i64;
+    let first_of_month = NaiveDate::from_ymd_opt(
+        months.div_euclid(12) as i32,
+        months.rem_euclid(12) as u32 + 1,
+        1,
+    )
+    .expect("the year is in range");
+    let date = first_of_month + Duration::days(local.day() as i64 - 1 - duration.days as i64);
+    let naive = NaiveDateTime::new(date, local.time());
+
+    let shifted = time
+        .timezone()
+        .from_local_datetime(&naive)
+        .earliest()
+        // In a gap of a daylight saving change, keep the offset of the original time.
+        .unwrap_or_else(|| {
+            let utc = naive - time.offset().fix();
+            time.timezone().from_utc_datetime(&utc)
+        });
+    shifted - Duration::hours(duration.hours as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, Utc};
+
+    fn snapshot(id: &str, time: &str, tags: &[&str]) -> Snapshot {
+        Snapshot {
+            id: id.to_owned(),
+            short_id: id.to_owned(),
+            time: time.parse().unwrap(),
+            parent: None,
+            paths: vec![],
+            hostname: "host".to_owned(),
+            username: String::new(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        }
+    }
+
+    /// Two years of snapshots, newest first: several a day at the end, then daily, weekly and monthly.
+    fn history() -> Vec<Snapshot> {
+        [
+            ("a", "2025-03-31T22:00:00Z", &[][..]),
+            ("b", "2025-03-31T20:30:00Z", &[]),
+            ("c", "2025-03-31T20:00:00Z", &[]),
+            ("d", "2025-03-31T08:00:00Z", &["pinned"]),
+            ("e", "2025-03-30T22:00:00Z", &[]),
+            ("f", "2025-03-29T22:00:00Z", &[]),
+            ("g", "2025-03-28T22:00:00Z", &["pinned", "manual"]),
+            ("h", "2025-03-23T22:00:00Z", &[]),
+            ("i", "2025-03-16T22:00:00Z", &[]),
+            ("j", "2025-03-02T22:00:00Z", &[]),
+            ("k", "2025-02-15T22:00:00Z", &[]),
+            ("l", "2025-01-15T22:00:00Z", &[]),
+            ("m", "2024-12-30T22:00:00Z", &[]),
+            ("n", "2024-12-28T22:00:00Z", &[]),
+            ("o", "2024-06-15T22:00:00Z", &[]),
+            ("p", "2023-06-15T22:00:00Z", &[]),
+        ]
+        .iter()
+        .map(|(id, time, tags)| snapshot(id, time, tags))
+        .collect()
+    }
+
+    fn ids(snapshots: &[Snapshot]) -> String {
+        snapshots
+            .iter()
+            .map(|snapshot| snapshot.id.as_str())
+            .collect()
+    }
+
+    fn duration(value: &str) -> Option<RetentionDuration> {
+        Some(value.parse().unwrap())
+    }
+
+    #[test]
+    fn keeps_snapshots_per_rule() {
+        let cases: Vec<(&str, RetentionPolicy, &str)> = vec![
+            (
+                "last",
+                RetentionPolicy {
+                    keep_last: Some(3),
+                    ..Default::default()
+                },
+                "abc",
+            ),
+            (
+                "last more than snapshots",
+                RetentionPolicy {
+                    keep_last: Some(100),
+                    ..Default::default()
+                },
+                "abcdefghijklmnop",
+            ),
+            (
+                "hourly",
+                RetentionPolicy {
+                    keep_hourly: Some(3),
+                    ..Default::default()
+                },
+                "abd",
+            ),
+            (
+                "daily",
+                RetentionPolicy {
+                    keep_daily: Some(3),
+                    ..Default::default()
+                },
+                "aef",
+            ),
+            (
+                // The ISO week of 2024-12-30 is the first week of 2025, 2024-12-28 is in the last week of 2024.
+                "weekly",
+                RetentionPolicy {
+                    keep_weekly: Some(9),
+                    ..Default::default()
+                },
+                "aehijklmn",
+            ),
+            (
+                "monthly",
+                RetentionPolicy {
+                    keep_monthly: Some(4),
+                    ..Default::default()
+                },
+                "aklm",
+            ),
+            (
+                "yearly",
+                RetentionPolicy {
+                    keep_yearly: Some(2),
+                    ..Default::default()
+                },
+                "am",
+            ),
+            (
+                // Counts left over keep the oldest snapshot.
+                "yearly with counts left",
+                RetentionPolicy {
+                    keep_yearly: Some(10),
+                    ..Default::default()
+                },
+                "amp",
+            ),
+            (
+                "zero counts are ignored",
+                RetentionPolicy {
+                    keep_last: Some(0),
+                    keep_daily: Some(2),
+                    ..Default::default()
+                },
+                "ae",
+            ),
+            (
+                // Only snapshots strictly newer than the newest minus the duration are kept.
+                "within",
+                RetentionPolicy {
+                    keep_within: duration("2d"),
+                    ..Default::default()
+                },
+                "abcde",
+            ),
+            (
+                "within hours",
+                RetentionPolicy {
+                    keep_within: duration("2h"),
+                    ..Default::default()
+                },
+                "ab",
+            ),
+            (
+                "within years",
+                RetentionPolicy {
+                    keep_within: duration("1y"),
+                    ..Default::default()
+                },
+                "abcdefghijklmno",
+            ),
+            (
+                "within daily",
+                RetentionPolicy {
+                    keep_within_daily: duration("3d"),
+                    ..Default::default()
+                },
+                "aef",
+            ),
+            (
+                "within hourly",
+                RetentionPolicy {
+                    keep_within_hourly: duration("1d"),
+                    ..Default::default()
+                },
+                "abd",
+            ),
+            (
+                "within weekly",
+                RetentionPolicy {
+                    keep_within_weekly: duration("1m"),
+                    ..Default::default()
+                },
+                "aehi",
+            ),
+            (
+                // Unlike the counts, the oldest snapshot is only kept when it is within the duration.
+                "within monthly",
+                RetentionPolicy {
+                    keep_within_monthly: duration("3m"),
+                    ..Default::default()
+                },
+                "akl",
+            ),
+            (
+                "within yearly",
+                RetentionPolicy {
+                    keep_within_yearly: duration("5y"),
+                    ..Default::default()
+                },
+                "amp",
+            ),
+            (
+                "tag",
+                RetentionPolicy {
+                    keep_tags: vec!["pinned".to_owned()],
+                    ..Default::default()
+                },
+                "dg",
+            ),
+            (
+                "all tags of the list",
+                RetentionPolicy {
+                    keep_tags: vec!["pinned,manual".to_owned()],
+                    ..Default::default()
+                },
+                "g",
+            ),
+            (
+                "any of the lists",
+                RetentionPolicy {
+                    keep_tags: vec!["pinned,manual".to_owned(), "pinned".to_owned()],
+                    ..Default::default()
+                },
+                "dg",
+            ),
+            (
+                "combined",
+                RetentionPolicy {
+                    keep_last: Some(2),
+                    keep_daily: Some(3),
+                    keep_monthly: Some(2),
+                    keep_tags: vec!["pinned".to_owned()],
+                    ..Default::default()
+                },
+                "abdefgk",
+            ),
+            (
+                "empty keeps all",
+                RetentionPolicy::default(),
+                "abcdefghijklmnop",
+            ),
+            (
+                "empty with remove all",
+                RetentionPolicy {
+                    unsafe_allow_remove_all: true,
+                    ..Default::default()
+                },
+                "",
+            ),
+            (
+                "remove all does not change a policy",
+                RetentionPolicy {
+                    keep_last: Some(1),
+                    unsafe_allow_remove_all: true,
+                    ..Default::default()
+                },
+                "a",
+            ),
+        ];
+
+        for (name, policy, expected) in cases {
+            let result = policy.simulate(&history(), &Utc);
+
+            assert_eq!(ids(&result.keep), expected, "{name}: kept");
+            assert_eq!(
+                result.keep.len() + result.remove.len(),
+                history().len(),
+                "{name}: every snapshot is kept or removed"
+            );
+            assert!(
+                result.remove.iter().all(|s| !expected.contains(&s.id)),
+                "{name}: removed"
+            );
+            assert_eq!(result.reasons.len(), result.keep.len(), "{name}: reasons");
+        }
+    }
+
+    #[test]
+    fn when_combined_then_every_reason_is_listed() {
+        let policy = RetentionPolicy {
+            keep_last: Some(1),
+            keep_daily: Some(1),
+            keep_within: duration("1d2h"),
+            keep_within_daily: duration("2d"),
+            keep_tags: vec!["pinned".to_owned()],
+            ..Default::default()
+        };
+
+        let result = policy.simulate(&history(), &Utc);
+
+        let reasons: Vec<(&str, Vec<&str>)> = result
+            .reasons
+            .iter()
+            .map(|reason| {
+                (
+                    reason.snapshot.id.as_str(),
+                    reason.matches.iter().map(String::as_str).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                (
+                    "a",
+                    vec![
+                        "within 1d2h",
+                        "last snapshot",
+                        "daily snapshot",
+                        "daily within 2d"
+                    ]
+                ),
+                ("b", vec!["within 1d2h"]),
+                ("c", vec!["within 1d2h"]),
+                ("d", vec!["has tags [pinned]", "within 1d2h"]),
+                ("e", vec!["within 1d2h", "daily within 2d"]),
+                ("g", vec!["has tags [pinned]"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn buckets_use_the_timezone() {
+        // 22:00 UTC is already the next day at UTC+3.
+        let timezone = FixedOffset::east_opt(3 * 3600).unwrap();
+        let policy = RetentionPolicy {
+            keep_daily: Some(3),
+            ..Default::default()
+        };
+
+        let in_utc = policy.simulate(&history(), &Utc);
+        let in_timezone = policy.simulate(&history(), &timezone);
+
+        assert_eq!(ids(&in_utc.keep), "aef");
+        // "a" is on April 1st at UTC+3, "b" to "e" are on March 31st and "f" on March 30th.
+        assert_eq!(ids(&in_timezone.keep), "abf");
+    }
+
+    #[test]
+    fn when_unsorted_then_newest_first() {
+        let mut snapshots = history();
+        snapshots.reverse();
+        let policy = RetentionPolicy {
+            keep_last: Some(2),
+            ..Default::default()
+        };
+
+        let result = policy.simulate(&snapshots, &Utc);
+
+        assert_eq!(ids(&result.keep), "ab");
+        assert_eq!(ids(&result.remove), "cdefghijklmnop");
+    }
+
+    #[test]
+    fn when_no_snapshots_then_nothing() {
+        let policy = RetentionPolicy {
+            keep_last: Some(2),
+            ..Default::default()
+        };
+
+        let result = policy.simulate(&[], &Utc);
+
+        assert_eq!(result, RetentionSimulation::default());
+        assert_eq!(result.covered(), None);
+    }
+
+    #[test]
+    fn covered_is_between_oldest_and_newest_kept() {
+        let policy = RetentionPolicy {
+            keep_daily: Some(3),
+            ..Default::default()
+        };
+
+        let result = policy.simulate(&history(), &Utc);
+
+        assert_eq!(result.covered(), Some(TimeDelta::days(2)));
+    }
+
+    #[test]
+    fn subtracts_like_restic() {
+        let cases = [
+            ("2025-03-31T12:00:00Z", "1m", "2025-03-03T12:00:00Z"),
+            ("2024-03-31T12:00:00Z", "1m", "2024-03-02T12:00:00Z"),
+            ("2025-03-15T12:00:00Z", "1y2m", "2024-01-15T12:00:00Z"),
+            ("2025-01-01T01:00:00Z", "1d2h", "2024-12-30T23:00:00Z"),
+            ("2024-02-29T00:00:00Z", "1y", "2023-03-01T00:00:00Z"),
+            ("2025-01-10T00:00:00Z", "40d", "2024-12-01T00:00:00Z"),
+        ];
+
+        for (time, duration, expected) in cases {
+            let time: DateTime<Utc> = time.parse().unwrap();
+            let duration: RetentionDuration = duration.parse().unwrap();
+
+            assert_eq!(
+                subtract(&time, &duration),
+                expected.parse::<DateTime<Utc>>().unwrap(),
+                "{time} - {duration}"
+            );
+        }
+    }
+}
@@ -2,6 +2,8 @@ mod common;
 
 use common::VirtualRepository;
 use restic_sdk::backup::BackupOptions;
+use restic_sdk::errors::ResticError;
+use restic_sdk::messages::BackupVerboseStatusAction;
 use tokio_util::sync::CancellationToken;
 
 #[tokio::test]
@@ -21,3 +23,156 @@ async fn command_backup() {
 
     assert!(summary.unwrap().summary.snapshot_id.is_some());
 }
+
+#[tokio::test]
+async fn command_backup_with_tags_and_host() {
+    let repository = VirtualRepository::new();
+
+    let restic = repository.get_client();
+    restic.init(&CancellationToken::new()).await.unwrap();
+
+    let summary = restic
+        .backup(
+            vec![repository.get_random_data_path().as_str()],
+            BackupOptions::new()
+                .with_tag("job:test")
+                .with_tag("static")
+                .with_host("test-host")
+                .with_time("2024-01-02 03:04:05"),
+            &CancellationToken::new(),
+        )
+        .await;
+
+    assert!(summary.unwrap().summary.snapshot_id.is_some());
+}
+
+#[tokio::test]
+async fn command_backup_with_excludes() {
+    let repository = VirtualRepository::new();
+
+    let restic = repository.get_client();
+    restic.init(&CancellationToken::new()).await.unwrap();
+
+    let summary = restic
+        .backup(
+            vec![repository.get_random_data_path().as_str()],
+            BackupOptions::new()
+                .with_exclude("fake_file")
+                .with_exclude_if_present(".nobackup")
+                .with_exclude_larger_than("1G"),
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(summary.summary.total_files_processed, 0);
+}
+
+#[tokio::test]
+async fn command_backup_stdin() {
+    let repository = VirtualRepository::new();
+
+    let restic = repository.get_client();
+    restic.init(&CancellationToken::new()).await.unwrap();
+
+    let summary = restic
+        .backup_stdin(
+            &b"some dumped data"[..],
+            BackupOptions::new().with_stdin_filename("dump.sql"),
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+    assert!(summary.summary.snapshot_id.is_some());
+    assert_eq!(summary.summary.total_files_processed, 1);
+}
+
+#[tokio::test]
+async fn command_backup_from_failing_command() {
+    let repository = VirtualRepository::new();
+
+    let restic = repository.get_client();
+    restic.init(&CancellationToken::new()).await.unwrap();
+
+    let result = restic
+        .backup_from_command(
+            ["restic", "not-a-command"],
+            BackupOptions::new(),
+            &CancellationToken::new(),
+        )
+        .await;
+
+    assert!(matches!(result, Err(ResticError::StdinCommandFailed(message)) if !message.is_empty()));
+}
+
+#[tokio::test]
+async fn command_backup_from_command_without_repository() {
+    let repository = VirtualRepository::new();
+
+    let restic = repository.get_client();
+    let result = restic
+        .backup_from_command(
+            ["restic", "version"],
+            BackupOptions::new(),
+            &CancellationToken::new(),
+        )
+        .await;
+
+    // The command did not fail, restic did, so it is not blamed on the command.
+    assert!(matches!(result, Err(ResticError::RepositoryDoesNotExist)));
+}
+
+#[tokio::test]
+async fn command_backup_skip_if_unchanged() {
+    let repository = VirtualRepository::new();
+
+    let restic = repository.get_client();
+    restic.init(&CancellationToken::new()).await.unwrap();
+
+    let path = repository.get_random_data_path();
+    let options = BackupOptions::new()
+        .with_skip_if_unchanged()
+        .with_no_scan()
+        .with_read_concurrency(4);
+    let first = restic
+        .backup(
+            vec![path.as_str()],
+            options.clone(),
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+    let second = restic
+        .backup(vec![path.as_str()], options, &CancellationToken::new())
+        .await
+        .unwrap();
+
+    assert!(!first.is_unchanged());
+    assert!(second.is_unchanged());
+}
+
+#[tokio::test]
+async fn command_backup_with_items() {
+    let repository = VirtualRepository::new();
+
+    let restic = repository.get_client();
+    restic.init(&CancellationToken::new()).await.unwrap();
+
+    let mut new_items = Vec::new();
+    restic
+        .backup_with_items(
+            vec![repository.get_random_data_path().as_str()],
+            BackupOptions::new().with_verbose_level(2),
+            |status| {
+                if status.action == BackupVerboseStatusAction::New {
+                    new_items.push(status.item);
+                }
+            },
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+    assert!(!new_items.is_empty());
+}
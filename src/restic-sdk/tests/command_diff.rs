@@ -0,0 +1,63 @@
+mod common;
+
+use common::VirtualRepository;
+use restic_sdk::backup::BackupOptions;
+use restic_sdk::diff::DiffOptions;
+use restic_sdk::snapshots::SnapshotsOptions;
+use std::fs::write;
+use std::path::Path;
+use tokio_util::sync::CancellationToken;
+
+#[tokio::test]
+async fn command_diff() {
+    let repository = VirtualRepository::new();
+
+    let restic = repository.get_client();
+    restic.init(&CancellationToken::new()).await.unwrap();
+
+    let random_data_path = repository.get_random_data_path();
+    restic
+        .backup(
+            vec![random_data_path.as_str()],
+            BackupOptions::new(),
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+    write(Path::new(&random_data_path).join("new_file"), "new").unwrap();
+    restic
+        .backup(
+            vec![random_data_path.as_str()],
+            BackupOptions::new(),
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+    let snapshots = restic
+        .snapshots(SnapshotsOptions::new(), &CancellationToken::new())
+        .await
+        .unwrap();
+    assert_eq!(snapshots.len(), 2);
+    assert_eq!(snapshots[1].parent.as_ref(), Some(&snapshots[0].id));
+
+    let mut changes = Vec::new();
+    let statistics = restic
+        .diff(
+            &snapshots[0].id,
+            &snapshots[1].id,
+            DiffOptions::new(),
+            |change| changes.push(change),
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(statistics.added.files, 1);
+    assert_eq!(statistics.removed.files, 0);
+    let added = changes
+        .iter()
+        .find(|change| change.path.ends_with("new_file"))
+        .expect("should report the new file");
+    assert!(added.is_added());
+}
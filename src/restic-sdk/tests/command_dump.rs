@@ -0,0 +1,73 @@
+mod common;
+
+use common::VirtualRepository;
+use restic_sdk::backup::BackupOptions;
+use restic_sdk::dump::{DumpArchive, DumpOptions};
+use restic_sdk::ls::LsOptions;
+use std::fs::read;
+use std::path::Path;
+use tokio_util::sync::CancellationToken;
+
+#[tokio::test]
+async fn command_dump() {
+    let repository = VirtualRepository::new();
+
+    let restic = repository.get_client();
+    restic.init(&CancellationToken::new()).await.unwrap();
+
+    let random_data_path = repository.get_random_data_path();
+    restic
+        .backup(
+            vec![random_data_path.as_str()],
+            BackupOptions::new(),
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+    // Paths in the snapshot differ from the local ones on Windows (e.g. "/C/Users").
+    let mut nodes = Vec::new();
+    restic
+        .ls(
+            "latest",
+            LsOptions::new(),
+            |node| nodes.push(node),
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+    let file = nodes
+        .iter()
+        .find(|node| node.name == "fake_file")
+        .expect("should list the file");
+
+    let mut contents = Vec::new();
+    restic
+        .dump(
+            "latest",
+            &file.path,
+            DumpOptions::new(),
+            &mut contents,
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        contents,
+        read(Path::new(&random_data_path).join("fake_file")).unwrap()
+    );
+
+    let directory = file.path.trim_end_matches("/fake_file");
+    let mut archive = Vec::new();
+    restic
+        .dump(
+            "latest",
+            directory,
+            DumpOptions::new().archive(DumpArchive::Zip),
+            &mut archive,
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+    assert!(archive.starts_with(b"PK"));
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use common::VirtualRepository;
+use restic_sdk::backup::BackupOptions;
+use restic_sdk::find::FindOptions;
+use tokio_util::sync::CancellationToken;
+
+#[tokio::test]
+async fn command_find() {
+    let repository = VirtualRepository::new();
+
+    let restic = repository.get_client();
+    restic.init(&CancellationToken::new()).await.unwrap();
+
+    let snapshot_id = restic
+        .backup(
+            vec![repository.get_random_data_path().as_str()],
+            BackupOptions::new(),
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap()
+        .summary
+        .snapshot_id
+        .unwrap();
+
+    let mut matches = Vec::new();
+    restic
+        .find(
+            "FAKE_*",
+            FindOptions::new().ignore_case(),
+            |snapshot, node| matches.push((snapshot.to_owned(), node)),
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(matches.len(), 1);
+    assert!(matches[0].0.starts_with(&snapshot_id[..8]));
+    assert!(matches[0].1.path.ends_with("fake_file"));
+}
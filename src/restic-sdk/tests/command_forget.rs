@@ -3,6 +3,7 @@ mod common;
 use common::VirtualRepository;
 use restic_sdk::backup::BackupOptions;
 use restic_sdk::forget::ForgetOptions;
+use restic_sdk::retention::MaxUnused;
 use tokio_util::sync::CancellationToken;
 
 #[tokio::test]
@@ -36,3 +37,82 @@ async fn command_forget() {
         )
         .await;
 }
+
+#[tokio::test]
+async fn command_forget_and_prune() {
+    let repository = VirtualRepository::new();
+
+    let restic = repository.get_client();
+    restic.init(&CancellationToken::new()).await.unwrap();
+
+    let random_data_path = repository.get_random_data_path();
+    for _ in 0..2 {
+        restic
+            .backup(
+                vec![random_data_path.as_str()],
+                BackupOptions::new(),
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+    }
+
+    let result = restic
+        .forget(
+            ForgetOptions::new()
+                .keep_last(1)
+                .prune()
+                .max_unused(MaxUnused::Percent(0.0)),
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result.kept().count(), 1);
+    assert_eq!(result.removed().count(), 1);
+    assert!(result.prune.is_some());
+}
+
+#[tokio::test]
+async fn command_forget_with_job_tag_and_untagged() {
+    let repository = VirtualRepository::new();
+
+    let restic = repository.get_client();
+    restic.init(&CancellationToken::new()).await.unwrap();
+
+    let random_data_path = repository.get_random_data_path();
+    let mut snapshot_ids = Vec::new();
+    for options in [
+        BackupOptions::new(),
+        BackupOptions::new().with_tag("job:a"),
+        BackupOptions::new().with_tag("job:b"),
+    ] {
+        let result = restic
+            .backup(
+                vec![random_data_path.as_str()],
+                options,
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        snapshot_ids.push(result.summary.snapshot_id.unwrap());
+    }
+
+    let result = restic
+        .forget(
+            ForgetOptions::new()
+                .keep_last(1)
+                .tag("job:a")
+                .tag("")
+                .dry_run(),
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+    // The snapshot made before job tags were added is forgotten, the one of the other job is not considered.
+    let kept: Vec<_> = result.kept().map(|(snapshot, _)| &snapshot.id).collect();
+    let removed: Vec<_> = result.removed().map(|snapshot| &snapshot.id).collect();
+    assert_eq!(kept, vec![&snapshot_ids[1]]);
+    assert_eq!(removed, vec![&snapshot_ids[0]]);
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use common::VirtualRepository;
+use restic_sdk::backup::BackupOptions;
+use restic_sdk::ls::LsOptions;
+use restic_sdk::messages::NodeType;
+use tokio_util::sync::CancellationToken;
+
+#[tokio::test]
+async fn command_ls() {
+    let repository = VirtualRepository::new();
+
+    let restic = repository.get_client();
+    restic.init(&CancellationToken::new()).await.unwrap();
+
+    restic
+        .backup(
+            vec![repository.get_random_data_path().as_str()],
+            BackupOptions::new(),
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+    let mut nodes = Vec::new();
+    let snapshot = restic
+        .ls(
+            "latest",
+            LsOptions::new(),
+            |node| nodes.push(node),
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+    assert!(!snapshot.id.is_empty());
+    let file = nodes
+        .iter()
+        .find(|node| node.name == "fake_file")
+        .expect("should list the file");
+    assert_eq!(file.node_type, NodeType::File);
+    assert_eq!(file.size, Some(16));
+}
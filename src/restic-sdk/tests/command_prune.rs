@@ -0,0 +1,36 @@
+mod common;
+
+use common::VirtualRepository;
+use restic_sdk::backup::BackupOptions;
+use restic_sdk::prune::PruneOptions;
+use restic_sdk::retention::MaxUnused;
+use tokio_util::sync::CancellationToken;
+
+#[tokio::test]
+async fn command_prune() {
+    let repository = VirtualRepository::new();
+
+    let restic = repository.get_client();
+    restic.init(&CancellationToken::new()).await.unwrap();
+
+    restic
+        .backup(
+            vec![repository.get_random_data_path().as_str()],
+            BackupOptions::new(),
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+    let result = restic
+        .prune(
+            PruneOptions::new()
+                .max_unused(MaxUnused::Percent(0.0))
+                .dry_run(),
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+    assert!(result.stats.is_some());
+}
@@ -0,0 +1,38 @@
+mod common;
+
+use common::VirtualRepository;
+use restic_sdk::backup::BackupOptions;
+use restic_sdk::stats::{StatsMode, StatsOptions};
+use tokio_util::sync::CancellationToken;
+
+#[tokio::test]
+async fn command_stats() {
+    let repository = VirtualRepository::new();
+
+    let restic = repository.get_client();
+    restic.init(&CancellationToken::new()).await.unwrap();
+
+    restic
+        .backup(
+            vec![repository.get_random_data_path().as_str()],
+            BackupOptions::new(),
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+    for mode in [
+        StatsMode::RestoreSize,
+        StatsMode::FilesByContents,
+        StatsMode::RawData,
+        StatsMode::BlobsPerFile,
+    ] {
+        let stats = restic
+            .stats(StatsOptions::new().mode(mode), &CancellationToken::new())
+            .await
+            .unwrap();
+
+        assert_eq!(stats.snapshots_count, 1, "{mode}");
+        assert!(stats.total_size > 0, "{mode}");
+    }
+}
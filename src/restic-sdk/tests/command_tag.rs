@@ -0,0 +1,46 @@
+mod common;
+
+use common::VirtualRepository;
+use restic_sdk::backup::BackupOptions;
+use restic_sdk::snapshots::SnapshotsOptions;
+use restic_sdk::tag::TagOptions;
+use tokio_util::sync::CancellationToken;
+
+#[tokio::test]
+async fn command_tag() {
+    let repository = VirtualRepository::new();
+
+    let restic = repository.get_client();
+    restic.init(&CancellationToken::new()).await.unwrap();
+
+    let result = restic
+        .backup(
+            vec![repository.get_random_data_path().as_str()],
+            BackupOptions::new(),
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+    let snapshot_id = result.summary.snapshot_id.unwrap();
+
+    let changed = restic
+        .tag(
+            &[&snapshot_id],
+            TagOptions::new().add_tags("pinned"),
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(changed.len(), 1);
+    assert_eq!(changed[0].old_snapshot_id, snapshot_id);
+    let snapshots = restic
+        .snapshots(
+            SnapshotsOptions::new().tag("pinned"),
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(snapshots.len(), 1);
+    assert_eq!(snapshots[0].id, changed[0].new_snapshot_id);
+}
@@ -0,0 +1,66 @@
+use actix_cors::Cors;
+
+/// Only lets the allowed origins call the API from a browser.
+///
+/// Any web page could otherwise read the backups, or unpin snapshots, through the browser of the user. Browsers send
+/// the origin with every POST and DELETE, so these are rejected before reaching a handler. Clients that are not
+/// browsers send no origin and are not affected.
+pub fn build_cors(allowed_origins: &[String]) -> Cors {
+    allowed_origins
+        .iter()
+        .fold(Cors::default(), |cors, origin| cors.allowed_origin(origin))
+        .allowed_methods(["GET", "POST", "DELETE"])
+        .allow_any_header()
+        .block_on_origin_mismatch(true)
+        .max_age(3600)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use actix_web::http::header::{ACCESS_CONTROL_ALLOW_ORIGIN, ORIGIN};
+    use actix_web::{App, HttpResponse, test, web};
+
+    async fn call(origin: Option<&str>) -> actix_web::dev::ServiceResponse {
+        let app = test::init_service(
+            App::new()
+                .wrap(build_cors(&["http://tauri.localhost".to_owned()]))
+                .route("/jobs", web::post().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let mut request = test::TestRequest::post().uri("/jobs");
+        if let Some(origin) = origin {
+            request = request.insert_header((ORIGIN, origin));
+        }
+        test::call_service(&app, request.to_request())
+            .await
+            .map_into_boxed_body()
+    }
+
+    #[actix_web::test]
+    async fn when_origin_allowed_then_called() {
+        let response = call(Some("http://tauri.localhost")).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(),
+            "http://tauri.localhost"
+        );
+    }
+
+    #[actix_web::test]
+    async fn when_origin_not_allowed_then_rejected() {
+        let response = call(Some("https://example.com")).await;
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn when_no_origin_then_called() {
+        let response = call(None).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,10 @@
+use crate::api::state::ApiState;
+use crate::config_status::ConfigurationStatusSnapshot;
+use actix_web::{get, web};
+
+#[get("/config/status")]
+pub async fn get_config_status(data: web::Data<ApiState>) -> web::Json<GetConfigStatusResponse> {
+    web::Json(data.configuration_status.snapshot())
+}
+
+pub type GetConfigStatusResponse = ConfigurationStatusSnapshot;
@@ -0,0 +1,239 @@
+use crate::api::endpoints::snapshots::get_client;
+use crate::api::errors::AppApiError;
+use crate::api::state::ApiState;
+use actix_web::http::header::{
+    Charset, ContentDisposition, DispositionParam, DispositionType, ExtendedValue,
+};
+use actix_web::{HttpResponse, get, web};
+use log::warn;
+use restic_sdk::dump::{DumpArchive, DumpOptions};
+use restic_sdk::errors::ResticError;
+use restic_sdk::ls::LsOptions;
+use restic_sdk::messages::{Node, NodeType};
+use serde::Deserialize;
+use std::io;
+use std::io::Cursor;
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+use tokio::io::{AsyncRead, AsyncReadExt, DuplexStream, ReadBuf};
+use tokio::task::JoinHandle;
+use tokio_util::io::ReaderStream;
+use tokio_util::sync::CancellationToken;
+
+/// How much of the output restic can write ahead of the download.
+const DUMP_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Downloads a file of a snapshot, or a directory as a tar or zip archive, streamed while restic reads it.
+#[get("/jobs/{id}/snapshots/{snapshot_id}/dump")]
+pub async fn dump_from_snapshot(
+    path: web::Path<(String, String)>,
+    query: Result<web::Query<DumpFromSnapshotQuery>, actix_web::Error>,
+    data: web::Data<ApiState>,
+) -> Result<HttpResponse, AppApiError> {
+    let (job_id, snapshot_id) = path.into_inner();
+    let query = query
+        .map_err(|e| AppApiError::InvalidRequest(e.to_string()))?
+        .into_inner();
+    let item_path = normalize_path(&query.path)?;
+    let client = get_client(&data, &job_id)?;
+
+    // Resolve the item first, so a wrong path is reported before the download starts.
+    let parent = match item_path.rsplit_once('/') {
+        Some(("", _)) | None => "/",
+        Some((parent, _)) => parent,
+    };
+    let mut item = None;
+    let snapshot = client
+        .ls(
+            &snapshot_id,
+            LsOptions::new().path(parent),
+            |node| {
+                if node.path == item_path {
+                    item = Some(node)
+                }
+            },
+            &CancellationToken::new(),
+        )
+        .await
+        .map_err(|e| {
+            warn!("Failed to list snapshot '{snapshot_id}' of job '{job_id}'. Error: {e}");
+            AppApiError::ResticFailed
+        })?;
+
+    let archive = query.archive.unwrap_or_default();
+    let download = match (&item, item_path.as_str()) {
+        (_, "/") => Download::archive(
+            &format!("snapshot-{}", snapshot.id.get(..8).unwrap_or(&snapshot.id)),
+            archive,
+        ),
+        (Some(node), _) if node.node_type == NodeType::Dir => {
+            Download::archive(&node.name, archive)
+        }
+        (Some(node), _) => Download::file(node),
+        (None, _) => return Err(AppApiError::PathNotFound),
+    };
+
+    let (output, reader) = tokio::io::duplex(DUMP_BUFFER_SIZE);
+    let options = DumpOptions::new().archive(archive.into());
+    let dump_snapshot_id = snapshot.id.clone();
+    let dump = actix_web::rt::spawn(async move {
+        // Restic stops when the download is dropped, as writing its output fails.
+        client
+            .dump(
+                &dump_snapshot_id,
+                &item_path,
+                options,
+                output,
+                &CancellationToken::new(),
+            )
+            .await
+    });
+    let mut reader = DumpReader {
+        output: reader,
+        dump: Some(dump),
+    };
+
+    // Wait for the first output, failures before it are still reported with a status.
+    let mut first_chunk = Vec::with_capacity(DUMP_BUFFER_SIZE);
+    reader.read_buf(&mut first_chunk).await.map_err(|e| {
+        warn!(
+            "Failed to dump '{}' of snapshot '{}' of job '{job_id}'. Error: {e}",
+            query.path, snapshot.id
+        );
+        AppApiError::ResticFailed
+    })?;
+
+    let mut response = HttpResponse::Ok();
+    response
+        .content_type(download.content_type)
+        .insert_header(content_disposition(&download.file_name));
+    if let Some(size) = download.size {
+        response.no_chunking(size);
+    }
+    Ok(response.streaming(ReaderStream::new(Cursor::new(first_chunk).chain(reader))))
+}
+
+/// Makes the path absolute, without a trailing slash.
+fn normalize_path(path: &str) -> Result<String, AppApiError> {
+    if !path.starts_with('/') {
+        return Err(AppApiError::InvalidRequest(
+            "path must be absolute, e.g. /C/Users".to_owned(),
+        ));
+    }
+    match path.trim_end_matches('/') {
+        "" => Ok("/".to_owned()),
+        path => Ok(path.to_owned()),
+    }
+}
+
+/// The file name as is for clients that support it, with non-ASCII characters replaced for the others.
+fn content_disposition(file_name: &str) -> ContentDisposition {
+    let fallback = file_name
+        .chars()
+        .map(|c| match c.is_ascii() && !c.is_ascii_control() {
+            true => c,
+            false => '_',
+        })
+        .collect();
+    let mut parameters = vec![DispositionParam::Filename(fallback)];
+    if !file_name.is_ascii() {
+        parameters.push(DispositionParam::FilenameExt(ExtendedValue {
+            charset: Charset::Ext("UTF-8".to_owned()),
+            language_tag: None,
+            value: file_name.as_bytes().to_vec(),
+        }));
+    }
+
+    ContentDisposition {
+        disposition: DispositionType::Attachment,
+        parameters,
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DumpFromSnapshotQuery {
+    /// The absolute path of the file or directory in the snapshot.
+    path: String,
+    /// The format directories are downloaded as, tar by default.
+    archive: Option<ArchiveFormat>,
+}
+
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ArchiveFormat {
+    #[default]
+    Tar,
+    Zip,
+}
+
+impl From<ArchiveFormat> for DumpArchive {
+    fn from(format: ArchiveFormat) -> Self {
+        match format {
+            ArchiveFormat::Tar => DumpArchive::Tar,
+            ArchiveFormat::Zip => DumpArchive::Zip,
+        }
+    }
+}
+
+struct Download {
+    file_name: String,
+    content_type: &'static str,
+    /// Only known for files, archives are built while they are downloaded.
+    size: Option<u64>,
+}
+
+impl Download {
+    fn file(node: &Node) -> Self {
+        Self {
+            file_name: node.name.clone(),
+            content_type: "application/octet-stream",
+            size: node.size,
+        }
+    }
+
+    fn archive(name: &str, format: ArchiveFormat) -> Self {
+        let (extension, content_type) = match format {
+            ArchiveFormat::Tar => ("tar", "application/x-tar"),
+            ArchiveFormat::Zip => ("zip", "application/zip"),
+        };
+        Self {
+            file_name: format!("{name}.{extension}"),
+            content_type,
+            size: None,
+        }
+    }
+}
+
+/// The output of a dump running in the background, failing at its end when the dump failed, so a truncated download
+/// is aborted instead of looking complete.
+struct DumpReader {
+    output: DuplexStream,
+    dump: Option<JoinHandle<Result<(), ResticError>>>,
+}
+
+impl AsyncRead for DumpReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let filled = buf.filled().len();
+        ready!(Pin::new(&mut self.output).poll_read(cx, buf))?;
+        if buf.filled().len() > filled {
+            return Poll::Ready(Ok(()));
+        }
+
+        // The output ended, the dump tells whether it is complete.
+        let Some(dump) = self.dump.as_mut() else {
+            return Poll::Ready(Ok(()));
+        };
+        let result = ready!(Pin::new(dump).poll(cx));
+        self.dump = None;
+        match result {
+            Ok(Ok(())) => Poll::Ready(Ok(())),
+            Ok(Err(e)) => Poll::Ready(Err(io::Error::other(e))),
+            Err(e) => Poll::Ready(Err(io::Error::other(e))),
+        }
+    }
+}
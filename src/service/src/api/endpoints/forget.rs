@@ -0,0 +1,116 @@
+use crate::api::errors::AppApiError;
+use crate::api::state::ApiState;
+use crate::jobs::{ForgetJob, JobRunner};
+use actix_web::{post, web};
+use common::config::ForgetConfiguration;
+use log::warn;
+use restic_sdk::messages::ForgetGroup;
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+
+/// Runs the forget policy of the job as a dry run, optionally with another policy, nothing is removed.
+#[post("/jobs/{id}/forget/preview")]
+pub async fn preview_job_forget(
+    path: web::Path<String>,
+    body: web::Bytes,
+    data: web::Data<ApiState>,
+) -> Result<web::Json<ForgetPreviewResponse>, AppApiError> {
+    let id = path.into_inner();
+    let Some((job_id, job)) = data
+        .job_manager
+        .get_jobs()
+        .into_iter()
+        .find(|(job_id, _)| job_id == &id)
+    else {
+        return Err(AppApiError::JobNotFound);
+    };
+
+    // The body is optional, without it the policy of the job is previewed.
+    let mut config = job.forget_and_purge.clone();
+    if !body.is_empty() {
+        let request: ForgetPreviewRequest = serde_json::from_slice(&body)
+            .map_err(|e| AppApiError::InvalidRequest(e.to_string()))?;
+        request.apply(&mut config);
+    }
+
+    let client = JobRunner::build_restic_client(&job).map_err(|e| {
+        warn!("Failed to configure restic for job '{job_id}'. Error: {e}");
+        AppApiError::InternalServerError
+    })?;
+
+    // A preview validates the policy like an enabled forget, even when forget is disabled.
+    config.enabled = true;
+    let forget =
+        ForgetJob::new(&job_id, &config).map_err(|e| AppApiError::InvalidRequest(e.to_string()))?;
+
+    // Dropping the request stops restic, so a cancelled request needs no token.
+    let result = forget
+        .preview(&client, &CancellationToken::new())
+        .await
+        .map_err(|e| {
+            warn!("Failed to preview forget of job '{job_id}'. Error: {e}");
+            AppApiError::ResticFailed
+        })?;
+
+    Ok(web::Json(ForgetPreviewResponse {
+        policy: forget.policy().to_string(),
+        keep_count: result.kept().count(),
+        remove_count: result.removed().count(),
+        job_id,
+        groups: result.groups,
+    }))
+}
+
+/// A retention policy replacing the one of the job, the filters (host, tag, path) of the job still apply.
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ForgetPreviewRequest {
+    group_by: Option<String>,
+    keep_last: Option<u32>,
+    keep_hourly: Option<u32>,
+    keep_daily: Option<u32>,
+    keep_weekly: Option<u32>,
+    keep_monthly: Option<u32>,
+    keep_yearly: Option<u32>,
+    keep_within: Option<String>,
+    keep_within_hourly: Option<String>,
+    keep_within_daily: Option<String>,
+    keep_within_weekly: Option<String>,
+    keep_within_monthly: Option<String>,
+    keep_within_yearly: Option<String>,
+    keep_tag: Option<String>,
+    unsafe_allow_remove_all: bool,
+}
+
+impl ForgetPreviewRequest {
+    fn apply(self, config: &mut ForgetConfiguration) {
+        if self.group_by.is_some() {
+            config.group_by = self.group_by;
+        }
+        config.keep_last = self.keep_last;
+        config.keep_hourly = self.keep_hourly;
+        config.keep_daily = self.keep_daily;
+        config.keep_weekly = self.keep_weekly;
+        config.keep_monthly = self.keep_monthly;
+        config.keep_yearly = self.keep_yearly;
+        config.keep_within = self.keep_within;
+        config.keep_within_hourly = self.keep_within_hourly;
+        config.keep_within_daily = self.keep_within_daily;
+        config.keep_within_weekly = self.keep_within_weekly;
+        config.keep_within_monthly = self.keep_within_monthly;
+        config.keep_within_yearly = self.keep_within_yearly;
+        config.keep_tag = self.keep_tag;
+        config.unsafe_allow_remove_all = self.unsafe_allow_remove_all;
+    }
+}
+
+#[derive(Serialize)]
+pub struct ForgetPreviewResponse {
+    job_id: String,
+    keep_count: usize,
+    remove_count: usize,
+    /// A summary of the previewed policy, e.g. "keep last 3, daily 7".
+    policy: String,
+    /// The snapshots kept and removed per group, with the rules that keep each snapshot.
+    groups: Vec<ForgetGroup>,
+}
@@ -1,12 +1,18 @@
+use crate::api::state::ApiState;
 use actix_web::{get, web};
 use serde::{Deserialize, Serialize};
 
 #[get("/health")]
-async fn health() -> web::Json<HealthResponse> {
-    web::Json(HealthResponse { ok: true })
+async fn health(data: web::Data<ApiState>) -> web::Json<HealthResponse> {
+    let configuration_error = data.configuration_status.snapshot().last_reload_error;
+    web::Json(HealthResponse {
+        ok: configuration_error.is_none(),
+        configuration_error,
+    })
 }
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct HealthResponse {
     ok: bool,
+    configuration_error: Option<String>,
 }
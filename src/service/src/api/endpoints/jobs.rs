@@ -1,15 +1,16 @@
 use crate::api::errors::AppApiError;
 use crate::api::state::ApiState;
 use crate::jobs::QueueJobError;
+use crate::runs::RunKind;
 use actix_web::{get, post, web};
-use common::config::ResticJob;
+use common::config::{PasswordSourceKind, ResticJob};
 use log::warn;
 use serde::Serialize;
 use std::collections::HashSet;
 
 #[get("/jobs")]
 pub async fn get_jobs(data: web::Data<ApiState>) -> web::Json<GetJobsResponse> {
-    let jobs = data.job_manager.get_job_names().cloned().collect();
+    let jobs = data.job_manager.get_job_names().into_iter().collect();
     web::Json(jobs)
 }
 
@@ -26,7 +27,8 @@ pub async fn get_job_by_id(
         .filter(|(job_id, _)| job_id == &id)
         .map(|(job_id, job)| GetJobByIdResponse {
             job_id,
-            job: sanitize_restic_job(job),
+            password_source: job.password_source().map(|source| source.kind()),
+            job: job.masked(),
         })
         .collect();
 
@@ -37,13 +39,35 @@ pub async fn get_job_by_id(
     Ok(web::Json(jobs.into_iter().next().unwrap()))
 }
 
+#[get("/jobs/{id}/effective-config")]
+pub async fn get_job_effective_config(
+    path: web::Path<String>,
+    data: web::Data<ApiState>,
+) -> Result<web::Json<GetJobEffectiveConfigResponse>, AppApiError> {
+    let id = path.into_inner();
+    let Some((job_id, job)) = data
+        .job_manager
+        .get_jobs()
+        .into_iter()
+        .find(|(job_id, _)| job_id == &id)
+    else {
+        return Err(AppApiError::JobNotFound);
+    };
+
+    Ok(web::Json(GetJobEffectiveConfigResponse {
+        job_id,
+        password_source: job.password_source().map(|source| source.kind()),
+        job: job.masked(),
+    }))
+}
+
 #[post("/jobs/{id}/queue")]
 pub async fn queue_job_by_id(
     path: web::Path<String>,
     data: web::Data<ApiState>,
 ) -> Result<web::Json<()>, AppApiError> {
     let id = path.into_inner();
-    match data.job_manager.queue_job(id).await {
+    match data.job_manager.queue_job(id, RunKind::Backup).await {
         Ok(_) => Ok(web::Json(())),
         Err(QueueJobError::JobNotFound(_)) => Err(AppApiError::JobNotFound),
         Err(QueueJobError::QueueSendError(e)) => {
@@ -60,11 +84,15 @@ pub type GetJobsResponse = HashSet<String>;
 #[derive(Serialize)]
 pub struct GetJobByIdResponse {
     job_id: String,
+    password_source: Option<PasswordSourceKind>,
+    /// The job with secrets masked, like the effective configuration.
     job: ResticJob,
 }
 
-fn sanitize_restic_job(job: ResticJob) -> ResticJob {
-    let mut sanitized_job = job.clone();
-    sanitized_job.password = "*".repeat(sanitized_job.password.len());
-    sanitized_job
+#[derive(Serialize)]
+pub struct GetJobEffectiveConfigResponse {
+    job_id: String,
+    password_source: Option<PasswordSourceKind>,
+    /// The job after defaults and templates are applied, with secrets masked.
+    job: ResticJob,
 }
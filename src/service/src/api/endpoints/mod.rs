@@ -1,5 +1,17 @@
+mod config;
+mod dump;
+mod forget;
 mod health;
 mod jobs;
+mod runs;
+mod snapshots;
+mod stats;
 
+pub use config::*;
+pub use dump::*;
+pub use forget::*;
 pub use health::*;
 pub use jobs::*;
+pub use runs::*;
+pub use snapshots::*;
+pub use stats::*;
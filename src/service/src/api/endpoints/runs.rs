@@ -0,0 +1,75 @@
+use crate::api::errors::AppApiError;
+use crate::api::state::ApiState;
+use crate::runs::{BackupItemError, JobRun};
+use actix_web::{get, web};
+use serde::Serialize;
+
+#[get("/jobs/{id}/runs")]
+pub async fn get_job_runs(
+    path: web::Path<String>,
+    data: web::Data<ApiState>,
+) -> Result<web::Json<GetJobRunsResponse>, AppApiError> {
+    let id = path.into_inner();
+    let runs = data.run_history.get_runs(&id);
+
+    // Runs of removed jobs are still served, until they age out of the history.
+    if runs.is_empty() && !data.job_manager.get_job_names().contains(&id) {
+        return Err(AppApiError::JobNotFound);
+    }
+
+    // Errors can be long, they are served per run.
+    let runs = runs
+        .into_iter()
+        .map(|mut run| {
+            if let Some(backup) = &mut run.backup {
+                backup.errors.clear();
+            }
+            run
+        })
+        .collect();
+
+    Ok(web::Json(runs))
+}
+
+#[get("/jobs/{id}/runs/{run_id}")]
+pub async fn get_job_run_by_id(
+    path: web::Path<(String, u64)>,
+    data: web::Data<ApiState>,
+) -> Result<web::Json<GetJobRunByIdResponse>, AppApiError> {
+    let (id, run_id) = path.into_inner();
+    match data.run_history.get_run(&id, run_id) {
+        Some(run) => Ok(web::Json(run)),
+        None => Err(AppApiError::RunNotFound),
+    }
+}
+
+#[get("/jobs/{id}/runs/{run_id}/errors")]
+pub async fn get_job_run_errors(
+    path: web::Path<(String, u64)>,
+    data: web::Data<ApiState>,
+) -> Result<web::Json<GetJobRunErrorsResponse>, AppApiError> {
+    let (id, run_id) = path.into_inner();
+    let Some(run) = data.run_history.get_run(&id, run_id) else {
+        return Err(AppApiError::RunNotFound);
+    };
+
+    let backup = run.backup.unwrap_or_default();
+    Ok(web::Json(GetJobRunErrorsResponse {
+        run_id,
+        error_count: backup.error_count,
+        errors: backup.errors,
+    }))
+}
+
+/// The runs of the job, most recent first, without the errors of their backup.
+pub type GetJobRunsResponse = Vec<JobRun>;
+
+pub type GetJobRunByIdResponse = JobRun;
+
+#[derive(Serialize)]
+pub struct GetJobRunErrorsResponse {
+    run_id: u64,
+    /// All errors reported, `errors` only has the first ones up to the configured maximum.
+    error_count: usize,
+    errors: Vec<BackupItemError>,
+}
@@ -0,0 +1,352 @@
+use crate::api::errors::AppApiError;
+use crate::api::state::ApiState;
+use crate::jobs::JobRunner;
+use actix_web::{delete, get, post, web};
+use common::config::ResticJob;
+use log::{info, warn};
+use restic_sdk::Restic;
+use restic_sdk::diff::DiffOptions;
+use restic_sdk::find::FindOptions;
+use restic_sdk::ls::LsOptions;
+use restic_sdk::messages::{DiffChange, DiffStatistics, Node};
+use restic_sdk::tag::TagOptions;
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+
+const DEFAULT_PAGE_SIZE: usize = 100;
+const MAX_PAGE_SIZE: usize = 1000;
+
+/// Lists the items directly in a directory of a snapshot, one page at a time.
+#[get("/jobs/{id}/snapshots/{snapshot_id}/tree")]
+pub async fn get_snapshot_tree(
+    path: web::Path<(String, String)>,
+    query: Result<web::Query<GetSnapshotTreeQuery>, actix_web::Error>,
+    data: web::Data<ApiState>,
+) -> Result<web::Json<GetSnapshotTreeResponse>, AppApiError> {
+    let (job_id, snapshot_id) = path.into_inner();
+    let query = query
+        .map_err(|e| AppApiError::InvalidRequest(e.to_string()))?
+        .into_inner();
+    let directory = query.path.unwrap_or_else(|| "/".to_owned());
+    let mut page = Page::new(query.offset, query.limit)?;
+    let client = get_client(&data, &job_id)?;
+
+    // Only the page is kept, restic streams the rest of the directory past it.
+    // Dropping the request stops restic, so a cancelled request needs no token.
+    let snapshot = client
+        .ls(
+            &snapshot_id,
+            LsOptions::new().path(&directory),
+            |node| page.push(node),
+            &CancellationToken::new(),
+        )
+        .await
+        .map_err(|e| {
+            warn!("Failed to list snapshot '{snapshot_id}' of job '{job_id}'. Error: {e}");
+            AppApiError::ResticFailed
+        })?;
+
+    Ok(web::Json(GetSnapshotTreeResponse {
+        job_id,
+        snapshot_id: snapshot.id,
+        path: directory,
+        page,
+    }))
+}
+
+/// Finds the items matching a pattern (e.g. "*.docx") in the snapshots of the repository, one page at a time.
+#[get("/jobs/{id}/find")]
+pub async fn find_in_job(
+    path: web::Path<String>,
+    query: Result<web::Query<FindInJobQuery>, actix_web::Error>,
+    data: web::Data<ApiState>,
+) -> Result<web::Json<FindInJobResponse>, AppApiError> {
+    let job_id = path.into_inner();
+    let query = query
+        .map_err(|e| AppApiError::InvalidRequest(e.to_string()))?
+        .into_inner();
+    if query.pattern.trim().is_empty() {
+        return Err(AppApiError::InvalidRequest(
+            "pattern must not be empty".to_owned(),
+        ));
+    }
+    let mut page = Page::new(query.offset, query.limit)?;
+    let client = get_client(&data, &job_id)?;
+
+    let mut options = FindOptions::new();
+    if query.ignore_case {
+        options = options.ignore_case();
+    }
+    if let Some(snapshot_id) = &query.snapshot {
+        options = options.snapshot(snapshot_id);
+    }
+
+    client
+        .find(
+            &query.pattern,
+            options,
+            |snapshot_id, node| {
+                page.push(FindMatch {
+                    snapshot_id: snapshot_id.to_owned(),
+                    node,
+                })
+            },
+            &CancellationToken::new(),
+        )
+        .await
+        .map_err(|e| {
+            warn!(
+                "Failed to find '{}' in job '{job_id}'. Error: {e}",
+                query.pattern
+            );
+            AppApiError::ResticFailed
+        })?;
+
+    Ok(web::Json(FindInJobResponse {
+        job_id,
+        pattern: query.pattern,
+        page,
+    }))
+}
+
+/// Lists the items added, removed or modified between two snapshots, one page at a time, with the statistics of all
+/// differences.
+#[get("/jobs/{id}/snapshots/{snapshot_id}/diff/{other_snapshot_id}")]
+pub async fn diff_snapshots(
+    path: web::Path<(String, String, String)>,
+    query: Result<web::Query<DiffSnapshotsQuery>, actix_web::Error>,
+    data: web::Data<ApiState>,
+) -> Result<web::Json<DiffSnapshotsResponse>, AppApiError> {
+    let (job_id, snapshot_id, other_snapshot_id) = path.into_inner();
+    let query = query
+        .map_err(|e| AppApiError::InvalidRequest(e.to_string()))?
+        .into_inner();
+    let mut page = Page::new(query.offset, query.limit)?;
+    let client = get_client(&data, &job_id)?;
+
+    let mut options = DiffOptions::new();
+    if query.metadata {
+        options = options.metadata();
+    }
+
+    let statistics = client
+        .diff(
+            &snapshot_id,
+            &other_snapshot_id,
+            options,
+            |change| page.push(change),
+            &CancellationToken::new(),
+        )
+        .await
+        .map_err(|e| {
+            warn!(
+                "Failed to diff snapshots '{snapshot_id}' and '{other_snapshot_id}' of job '{job_id}'. Error: {e}"
+            );
+            AppApiError::ResticFailed
+        })?;
+
+    Ok(web::Json(DiffSnapshotsResponse {
+        job_id,
+        statistics,
+        page,
+    }))
+}
+
+/// Pins a snapshot, tagging it with the `pin_tag` of the job so that forget always keeps it.
+#[post("/jobs/{id}/snapshots/{snapshot_id}/pin")]
+pub async fn pin_snapshot(
+    path: web::Path<(String, String)>,
+    data: web::Data<ApiState>,
+) -> Result<web::Json<PinSnapshotResponse>, AppApiError> {
+    let (job_id, snapshot_id) = path.into_inner();
+    change_pin(&data, job_id, snapshot_id, true).await
+}
+
+/// Unpins a snapshot, removing the `pin_tag` of the job, forget keeps it only when the policy does.
+#[delete("/jobs/{id}/snapshots/{snapshot_id}/pin")]
+pub async fn unpin_snapshot(
+    path: web::Path<(String, String)>,
+    data: web::Data<ApiState>,
+) -> Result<web::Json<PinSnapshotResponse>, AppApiError> {
+    let (job_id, snapshot_id) = path.into_inner();
+    change_pin(&data, job_id, snapshot_id, false).await
+}
+
+async fn change_pin(
+    data: &ApiState,
+    job_id: String,
+    snapshot_id: String,
+    pinned: bool,
+) -> Result<web::Json<PinSnapshotResponse>, AppApiError> {
+    let job = get_job(data, &job_id)?;
+    let pin_tag = job.forget_and_purge.pin_tag.clone();
+    if pin_tag.is_empty() {
+        return Err(AppApiError::InvalidRequest(
+            "pinning is disabled, the pin_tag of the job is empty".to_owned(),
+        ));
+    }
+    let client = build_client(&job, &job_id)?;
+
+    let options = match pinned {
+        true => TagOptions::new().add_tags(&pin_tag),
+        false => TagOptions::new().remove_tags(&pin_tag),
+    };
+    let changed = client
+        .tag(&[&snapshot_id], options, &CancellationToken::new())
+        .await
+        .map_err(|e| {
+            warn!("Failed to tag snapshot '{snapshot_id}' of job '{job_id}'. Error: {e}");
+            AppApiError::ResticFailed
+        })?;
+
+    // Restic replaces a snapshot whose tags change, nothing changes when it already is (un)pinned.
+    let new_snapshot_id = changed
+        .into_iter()
+        .next()
+        .map(|snapshot| snapshot.new_snapshot_id);
+    info!(
+        "Snapshot '{snapshot_id}' of job '{job_id}' is {}, new snapshot: {new_snapshot_id:?}.",
+        match pinned {
+            true => "pinned",
+            false => "unpinned",
+        }
+    );
+
+    Ok(web::Json(PinSnapshotResponse {
+        job_id,
+        snapshot_id,
+        new_snapshot_id,
+        pinned,
+        pin_tag,
+    }))
+}
+
+fn get_job(data: &ApiState, job_id: &str) -> Result<ResticJob, AppApiError> {
+    data.job_manager
+        .get_jobs()
+        .into_iter()
+        .find(|(id, _)| id == job_id)
+        .map(|(_, job)| job)
+        .ok_or(AppApiError::JobNotFound)
+}
+
+pub(super) fn get_client(data: &ApiState, job_id: &str) -> Result<Restic, AppApiError> {
+    build_client(&get_job(data, job_id)?, job_id)
+}
+
+fn build_client(job: &ResticJob, job_id: &str) -> Result<Restic, AppApiError> {
+    JobRunner::build_restic_client(job).map_err(|e| {
+        warn!("Failed to configure restic for job '{job_id}'. Error: {e}");
+        AppApiError::InternalServerError
+    })
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GetSnapshotTreeQuery {
+    /// The directory to list, the root of the snapshot by default.
+    path: Option<String>,
+    #[serde(default)]
+    offset: usize,
+    limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct GetSnapshotTreeResponse {
+    job_id: String,
+    snapshot_id: String,
+    path: String,
+    #[serde(flatten)]
+    page: Page<Node>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FindInJobQuery {
+    pattern: String,
+    #[serde(default)]
+    ignore_case: bool,
+    /// Only search this snapshot, all snapshots by default.
+    snapshot: Option<String>,
+    #[serde(default)]
+    offset: usize,
+    limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct FindInJobResponse {
+    job_id: String,
+    pattern: String,
+    #[serde(flatten)]
+    page: Page<FindMatch>,
+}
+
+#[derive(Serialize)]
+pub struct FindMatch {
+    snapshot_id: String,
+    #[serde(flatten)]
+    node: Node,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DiffSnapshotsQuery {
+    /// Also lists the items whose metadata changed but not their contents.
+    #[serde(default)]
+    metadata: bool,
+    #[serde(default)]
+    offset: usize,
+    limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct DiffSnapshotsResponse {
+    job_id: String,
+    statistics: DiffStatistics,
+    #[serde(flatten)]
+    page: Page<DiffChange>,
+}
+
+#[derive(Serialize)]
+pub struct PinSnapshotResponse {
+    job_id: String,
+    snapshot_id: String,
+    /// The ID of the snapshot replacing the one given, `None` when it already was (un)pinned.
+    new_snapshot_id: Option<String>,
+    pinned: bool,
+    pin_tag: String,
+}
+
+/// The items from `offset` to `offset + limit`, counting all items pushed.
+#[derive(Serialize)]
+pub struct Page<T> {
+    offset: usize,
+    limit: usize,
+    total: usize,
+    items: Vec<T>,
+}
+
+impl<T> Page<T> {
+    fn new(offset: usize, limit: Option<usize>) -> Result<Self, AppApiError> {
+        let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE);
+        if limit == 0 || limit > MAX_PAGE_SIZE {
+            return Err(AppApiError::InvalidRequest(format!(
+                "limit must be between 1 and {MAX_PAGE_SIZE}"
+            )));
+        }
+
+        Ok(Self {
+            offset,
+            limit,
+            total: 0,
+            items: Vec::new(),
+        })
+    }
+
+    fn push(&mut self, item: T) {
+        if self.total >= self.offset && self.items.len() < self.limit {
+            self.items.push(item);
+        }
+        self.total += 1;
+    }
+}
@@ -0,0 +1,58 @@
+use crate::api::errors::AppApiError;
+use crate::api::state::ApiState;
+use crate::runs::{HostStatsSummary, StatsSample};
+use actix_web::{get, web};
+use common::config::StatsMode;
+use serde::{Deserialize, Serialize};
+
+/// The statistics taken by the stats runs of the job, most recent first.
+#[get("/jobs/{id}/stats")]
+pub async fn get_job_stats(
+    path: web::Path<String>,
+    query: Result<web::Query<GetJobStatsQuery>, actix_web::Error>,
+    data: web::Data<ApiState>,
+) -> Result<web::Json<GetJobStatsResponse>, AppApiError> {
+    let id = path.into_inner();
+    let query = query
+        .map_err(|e| AppApiError::InvalidRequest(e.to_string()))?
+        .into_inner();
+    let samples = data.stats_history.get_samples(&id);
+
+    // Samples of removed jobs are still served, like their runs.
+    if samples.is_empty() && !data.job_manager.get_job_names().contains(&id) {
+        return Err(AppApiError::JobNotFound);
+    }
+
+    let summary = HostStatsSummary::from_latest(&samples);
+    let samples = samples
+        .into_iter()
+        .filter(|sample| query.mode.is_none_or(|mode| sample.mode == mode))
+        .filter(|sample| {
+            query
+                .host
+                .as_ref()
+                .is_none_or(|host| sample.host.as_ref() == Some(host))
+        })
+        .collect();
+
+    Ok(web::Json(GetJobStatsResponse {
+        job_id: id,
+        summary,
+        samples,
+    }))
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GetJobStatsQuery {
+    mode: Option<StatsMode>,
+    host: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct GetJobStatsResponse {
+    job_id: String,
+    /// The latest size and deduplication of every host.
+    summary: Vec<HostStatsSummary>,
+    samples: Vec<StatsSample>,
+}
@@ -7,6 +7,14 @@ use thiserror::Error;
 pub enum AppApiError {
     #[error("Job not found")]
     JobNotFound,
+    #[error("Run not found")]
+    RunNotFound,
+    #[error("Path not found in the snapshot")]
+    PathNotFound,
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+    #[error("Restic failed, see the service log for details")]
+    ResticFailed,
     #[error("Internal server error")]
     InternalServerError,
 }
@@ -15,15 +23,22 @@ impl error::ResponseError for AppApiError {
     fn status_code(&self) -> StatusCode {
         match *self {
             AppApiError::JobNotFound => StatusCode::NOT_FOUND,
+            AppApiError::RunNotFound => StatusCode::NOT_FOUND,
+            AppApiError::PathNotFound => StatusCode::NOT_FOUND,
+            AppApiError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+            AppApiError::ResticFailed => StatusCode::BAD_GATEWAY,
             AppApiError::InternalServerError => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
     fn error_response(&self) -> HttpResponse {
         HttpResponse::build(self.status_code())
             .insert_header(ContentType::json())
-            .body(format!(
-                r#"{{"message":"{self}","code":"{code}"}}"#,
-                code = self.status_code().as_u16()
-            ))
+            .body(
+                serde_json::json!({
+                    "message": self.to_string(),
+                    "code": self.status_code().as_u16().to_string(),
+                })
+                .to_string(),
+            )
     }
 }
@@ -1,3 +1,4 @@
+mod cors;
 mod endpoints;
 mod errors;
 mod server;
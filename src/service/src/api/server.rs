@@ -1,7 +1,14 @@
-use crate::api::endpoints::{get_job_by_id, get_jobs, health, queue_job_by_id};
+use crate::api::cors::build_cors;
+use crate::api::endpoints::{
+    diff_snapshots, dump_from_snapshot, find_in_job, get_config_status, get_job_by_id,
+    get_job_effective_config, get_job_run_by_id, get_job_run_errors, get_job_runs, get_job_stats,
+    get_jobs, get_snapshot_tree, health, pin_snapshot, preview_job_forget, queue_job_by_id,
+    unpin_snapshot,
+};
 use crate::api::state::ApiState;
+use crate::config_status::ConfigurationStatus;
 use crate::jobs::JobManager;
-use actix_cors::Cors;
+use crate::runs::{RunHistory, StatsHistory};
 use actix_web::{App, HttpServer, web};
 use common::config::ApiConfiguration;
 use std::sync::Arc;
@@ -10,6 +17,9 @@ use tokio_util::sync::CancellationToken;
 pub async fn run_server(
     config: &ApiConfiguration,
     job_manager: &Arc<JobManager>,
+    configuration_status: &Arc<ConfigurationStatus>,
+    run_history: &Arc<RunHistory>,
+    stats_history: &Arc<StatsHistory>,
     cancellation_token: &CancellationToken,
 ) -> std::io::Result<()> {
     if !config.enabled {
@@ -17,24 +27,40 @@ pub async fn run_server(
     }
 
     let server_cancellation_token = cancellation_token.child_token();
+    let allowed_origins = config.allowed_origins.clone();
     let server = HttpServer::new({
         let job_manager = job_manager.clone();
+        let configuration_status = configuration_status.clone();
+        let run_history = run_history.clone();
+        let stats_history = stats_history.clone();
         move || {
-            let cors = Cors::default()
-                .allow_any_origin()
-                .allow_any_header()
-                .allow_any_method()
-                .max_age(3600);
+            let cors = build_cors(&allowed_origins);
 
             let api = web::scope("/api/v1")
                 .wrap(cors)
                 .app_data(web::Data::new(ApiState {
                     job_manager: job_manager.clone(),
+                    configuration_status: configuration_status.clone(),
+                    run_history: run_history.clone(),
+                    stats_history: stats_history.clone(),
                 }))
                 .service(health)
+                .service(get_config_status)
                 .service(get_jobs)
                 .service(get_job_by_id)
-                .service(queue_job_by_id);
+                .service(get_job_effective_config)
+                .service(queue_job_by_id)
+                .service(preview_job_forget)
+                .service(get_job_runs)
+                .service(get_job_run_by_id)
+                .service(get_job_run_errors)
+                .service(get_job_stats)
+                .service(get_snapshot_tree)
+                .service(find_in_job)
+                .service(diff_snapshots)
+                .service(dump_from_snapshot)
+                .service(pin_snapshot)
+                .service(unpin_snapshot);
 
             App::new().service(api)
         }
@@ -1,6 +1,11 @@
+use crate::config_status::ConfigurationStatus;
 use crate::jobs::JobManager;
+use crate::runs::{RunHistory, StatsHistory};
 use std::sync::Arc;
 
 pub struct ApiState {
     pub job_manager: Arc<JobManager>,
+    pub configuration_status: Arc<ConfigurationStatus>,
+    pub run_history: Arc<RunHistory>,
+    pub stats_history: Arc<StatsHistory>,
 }
@@ -6,6 +6,9 @@ pub struct Args {
     /// The operation to perform in cli mode.
     #[arg(value_enum)]
     pub verb: Verb,
+
+    /// The job to show, for verbs that target jobs. All jobs are shown when omitted.
+    pub job: Option<String>,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
@@ -24,6 +27,8 @@ pub enum Verb {
     Status,
     /// Run the service in the foreground.
     Run,
+    /// Print the configuration of jobs after defaults and templates are applied, secrets are masked.
+    ShowEffectiveConfig,
 }
 
 pub fn parse_args() -> Args {
@@ -0,0 +1,43 @@
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::sync::RwLock;
+
+/// Tracks the outcome of loading and reloading the configuration file.
+#[derive(Default)]
+pub struct ConfigurationStatus {
+    inner: RwLock<ConfigurationStatusSnapshot>,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct ConfigurationStatusSnapshot {
+    /// When the running configuration was loaded.
+    pub loaded_at: Option<DateTime<Local>>,
+    /// When the configuration file was last (re)read, successfully or not.
+    pub last_reload_at: Option<DateTime<Local>>,
+    /// The error of the last reload, if the running configuration is stale.
+    pub last_reload_error: Option<String>,
+}
+
+impl ConfigurationStatus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_loaded(&self) {
+        let now = Local::now();
+        let mut status = self.inner.write().unwrap();
+        status.loaded_at = Some(now);
+        status.last_reload_at = Some(now);
+        status.last_reload_error = None;
+    }
+
+    pub fn set_reload_failed(&self, error: impl Into<String>) {
+        let mut status = self.inner.write().unwrap();
+        status.last_reload_at = Some(Local::now());
+        status.last_reload_error = Some(error.into());
+    }
+
+    pub fn snapshot(&self) -> ConfigurationStatusSnapshot {
+        self.inner.read().unwrap().clone()
+    }
+}
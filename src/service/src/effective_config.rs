@@ -0,0 +1,48 @@
+use common::config::{ResticJob, ServiceConfigurationManager};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::process::ExitCode;
+
+#[derive(Serialize)]
+struct EffectiveConfiguration {
+    jobs: BTreeMap<String, ResticJob>,
+}
+
+/// Prints the resolved jobs as TOML, or only the given job.
+pub async fn show_effective_config(job_id: Option<&str>) -> ExitCode {
+    let config = match ServiceConfigurationManager::new()
+        .read_configuration()
+        .await
+    {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let jobs: BTreeMap<_, _> = config
+        .jobs
+        .into_iter()
+        .filter(|(id, _)| job_id.is_none_or(|job_id| job_id == id))
+        .map(|(id, job)| (id, job.masked()))
+        .collect();
+
+    if let Some(job_id) = job_id
+        && jobs.is_empty()
+    {
+        eprintln!("Error: Job '{job_id}' is not defined");
+        return ExitCode::FAILURE;
+    }
+
+    match toml::to_string_pretty(&EffectiveConfiguration { jobs }) {
+        Ok(toml) => {
+            println!("{toml}");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -1,17 +1,29 @@
 use crate::api::run_server;
-use crate::jobs::{JobManager, JobRunner};
-use async_cron_scheduler::{Job, Scheduler};
+use crate::config_status::ConfigurationStatus;
+use crate::jobs::{ForgetJob, JobManager, JobRunner, PruneJob, QueuedJob};
+use crate::paths::get_exe_directory;
+use crate::runs::{RunHistory, RunKind, StatsHistory};
+use async_cron_scheduler::{Job, JobId, Scheduler};
 use chrono::Local;
-use common::config::{ResticJob, ServiceConfiguration, ServiceConfigurationManager};
-use log::{info, warn};
+use common::config::{
+    ApiConfiguration, ConfigurationDiff, ConfigurationError, ConfigurationWithWatcher,
+    ServiceConfiguration, ServiceConfigurationManager,
+};
+use log::{error, info, warn};
+use std::collections::HashMap;
 use std::ffi::OsString;
 use std::sync::Arc;
 use tokio::runtime::Handle;
-use tokio::sync::mpsc::channel;
+use tokio::sync::mpsc::{Receiver, channel};
 use tokio::task;
+use tokio::task::JoinHandle;
 use tokio::time::Instant;
 use tokio_util::sync::CancellationToken;
 
+/// Name of the file next to the executable the run history is persisted to.
+const RUN_HISTORY_FILE: &str = "run_history.json";
+const STATS_HISTORY_FILE: &str = "stats_history.json";
+
 pub struct ServiceHost;
 
 impl ServiceHost {
@@ -21,108 +33,312 @@ impl ServiceHost {
             .await
             .expect("configuration file must exist to watch");
 
+        let configuration_status = Arc::new(ConfigurationStatus::new());
+        let run_history =
+            Arc::new(RunHistory::load(get_exe_directory().join(RUN_HISTORY_FILE)).await);
+        let stats_history =
+            Arc::new(StatsHistory::load(get_exe_directory().join(STATS_HISTORY_FILE)).await);
+
+        let (sender, receiver) = channel::<QueuedJob>(256);
+        let job_manager_ref = Arc::new(JobManager::new(sender, cancellation_token));
+        let jobs_task = task::spawn(Self::run_worker(
+            receiver,
+            run_history.clone(),
+            stats_history.clone(),
+            cancellation_token.clone(),
+        ));
+
+        let (scheduler, sched_service) = Scheduler::<Local>::launch(tokio::time::sleep);
+        let cron_task = task::spawn(sched_service);
+
+        let mut running = RunningConfiguration {
+            config: None,
+            scheduler,
+            schedules: HashMap::new(),
+            server: None,
+            job_manager_ref,
+            configuration_status: configuration_status.clone(),
+            run_history,
+            stats_history,
+        };
+
         while !cancellation_token.is_cancelled() {
-            let configuration_cancellation_token = &cancellation_token.child_token();
-            watcher.register_cancellation_token(configuration_cancellation_token);
+            let change_token = CancellationToken::new();
+            watcher.register_cancellation_token(&change_token);
 
-            match watcher.read_configuration().await {
-                Ok(config) => Self::run_with_config(config, configuration_cancellation_token).await,
+            match Self::read_configuration(&watcher).await {
+                Ok(config) => {
+                    running.apply(config, cancellation_token).await;
+                    configuration_status.set_loaded();
+                }
+                Err(e) if running.config.is_some() => {
+                    warn!(
+                        "Configuration error, keeping the previous configuration running... Error: {e:?}"
+                    );
+                    configuration_status.set_reload_failed(e.to_string());
+                }
                 Err(e) => {
                     warn!("Configuration error, waiting for next update... Error: {e:?}");
-                    configuration_cancellation_token.cancelled().await;
+                    configuration_status.set_reload_failed(e.to_string());
                 }
             };
+
+            tokio::select! {
+                _ = change_token.cancelled() => {}
+                _ = cancellation_token.cancelled() => {}
+            }
         }
 
+        info!("Cancellation triggered, stopping jobs...");
+        running.stop_server().await;
+        // Drops the scheduler...
+        drop(running);
+
+        jobs_task.await.unwrap();
+        cron_task.await.unwrap();
+
         info!("Stopped the service host successfully.");
 
         0
     }
 
-    async fn run_with_config(config: ServiceConfiguration, cancellation_token: &CancellationToken) {
-        let (sender, mut receiver) = channel::<(String, ResticJob)>(256);
-        let job_manager_ref = Arc::new(JobManager::new(config.clone(), sender));
+    /// Reads and validates the configuration, without touching the running configuration.
+    async fn read_configuration(
+        watcher: &ConfigurationWithWatcher,
+    ) -> Result<ServiceConfiguration, ConfigurationError> {
+        let config = watcher.read_configuration().await?;
 
-        let (mut scheduler, sched_service) = Scheduler::<Local>::launch(tokio::time::sleep);
+        for (job_name, job_config) in &config.jobs {
+            if let Err(e) = Job::<Local>::cron(&format!("0 {}", job_config.cron)) {
+                return Err(ConfigurationError::InvalidConfiguration(format!(
+                    "job '{job_name}' has an invalid cron expression '{}': {e}",
+                    job_config.cron
+                )));
+            }
 
-        let cron_task = task::spawn(sched_service);
+            if let Some(prune_cron) = &job_config.prune.cron
+                && let Err(e) = Job::<Local>::cron(&format!("0 {prune_cron}"))
+            {
+                return Err(ConfigurationError::InvalidConfiguration(format!(
+                    "job '{job_name}' has an invalid prune cron expression '{prune_cron}': {e}"
+                )));
+            }
+            if let Some(stats_cron) = &job_config.stats.cron
+                && let Err(e) = Job::<Local>::cron(&format!("0 {stats_cron}"))
+            {
+                return Err(ConfigurationError::InvalidConfiguration(format!(
+                    "job '{job_name}' has an invalid stats cron expression '{stats_cron}': {e}"
+                )));
+            }
 
-        let jobs_task = task::spawn({
-            let cancellation_token = cancellation_token.clone();
-            async move {
-                while !cancellation_token.is_cancelled() {
-                    tokio::select! {
-                        job = receiver.recv() => {
-                            if let Some((job_name, job_config)) = job {
-                                info!("Job '{job_name}' is running.");
-                                let start = Instant::now();
-
-                                JobRunner::run(&job_config, &cancellation_token).await;
-
-                                info!(
-                                    "Job '{job_name}' is stopped after running for {:?}.",
-                                    start.elapsed()
-                                );
-                            }
+            // Policies and sizes are parsed here, so a typo does not wait for the next run to fail.
+            if let Err(e) = ForgetJob::new(job_name, &job_config.forget_and_purge) {
+                return Err(ConfigurationError::InvalidConfiguration(format!(
+                    "job '{job_name}' has an invalid forget policy: {e}"
+                )));
+            }
+            if job_config.prune.enabled
+                && let Err(e) = PruneJob::new(&job_config.prune)
+            {
+                return Err(ConfigurationError::InvalidConfiguration(format!(
+                    "job '{job_name}' has an invalid prune configuration: {e}"
+                )));
+            }
+        }
+
+        Ok(config)
+    }
+
+    async fn run_worker(
+        mut receiver: Receiver<QueuedJob>,
+        run_history: Arc<RunHistory>,
+        stats_history: Arc<StatsHistory>,
+        cancellation_token: CancellationToken,
+    ) {
+        while !cancellation_token.is_cancelled() {
+            tokio::select! {
+                job = receiver.recv() => {
+                    if let Some(job) = job {
+                        if job.cancellation_token.is_cancelled() {
+                            info!("Job '{}' was changed or removed while queued, skipping.", job.job_id);
+                            continue;
                         }
-                        _ = cancellation_token.cancelled() => { }
+
+                        info!("Job '{}' is running.", job.job_id);
+                        let start = Instant::now();
+
+                        JobRunner::run(
+                            &job.job_id,
+                            &job.config,
+                            job.kind,
+                            &run_history,
+                            &stats_history,
+                            &job.cancellation_token,
+                        )
+                        .await;
+
+                        info!(
+                            "Job '{}' is stopped after running for {:?}.",
+                            job.job_id,
+                            start.elapsed()
+                        );
                     }
                 }
+                _ = cancellation_token.cancelled() => { }
+            }
+        }
+
+        info!("Job worker is stopped.");
+    }
+}
+
+/// The parts of the host that are updated in place when the configuration changes.
+struct RunningConfiguration {
+    config: Option<ServiceConfiguration>,
+    scheduler: Scheduler<Local>,
+    schedules: HashMap<String, Vec<JobId>>,
+    server: Option<(CancellationToken, JoinHandle<()>)>,
+    job_manager_ref: Arc<JobManager>,
+    configuration_status: Arc<ConfigurationStatus>,
+    run_history: Arc<RunHistory>,
+    stats_history: Arc<StatsHistory>,
+}
+
+impl RunningConfiguration {
+    /// Applies only the differences between the running and the new configuration,
+    /// jobs that did not change keep running undisturbed.
+    async fn apply(
+        &mut self,
+        config: ServiceConfiguration,
+        cancellation_token: &CancellationToken,
+    ) {
+        let diff = ConfigurationDiff::between(self.config.as_ref(), &config);
+        if diff.is_empty() {
+            info!("Configuration did not change in any meaningful way.");
+            self.config = Some(config);
+            return;
+        }
+
+        for job_name in &diff.removed_jobs {
+            info!("Removing job '{job_name}'...");
+            self.unschedule(job_name).await;
+            self.job_manager_ref.remove_job(job_name);
+        }
+
+        for job_name in diff.changed_jobs.iter().chain(&diff.added_jobs) {
+            let job_config = config.jobs[job_name].clone();
+            self.unschedule(job_name).await;
+            self.job_manager_ref
+                .upsert_job(job_name, job_config.clone());
 
-                info!("Job worker is stopped.");
+            info!(
+                "Scheduling job '{job_name}' with cron: '{}'.",
+                job_config.cron
+            );
+            self.schedule(job_name, &job_config.cron, RunKind::Backup)
+                .await;
+
+            if job_config.prune.enabled
+                && let Some(prune_cron) = &job_config.prune.cron
+            {
+                info!("Scheduling prune of job '{job_name}' with cron: '{prune_cron}'.");
+                self.schedule(job_name, prune_cron, RunKind::Prune).await;
             }
-        });
 
-        let server_task = task::spawn({
-            let job_manager_ref = job_manager_ref.clone();
-            let cancellation_token = cancellation_token.clone();
-            async move {
-                run_server(&config.api, &job_manager_ref, &cancellation_token)
-                    .await
-                    .unwrap();
+            if job_config.stats.enabled
+                && let Some(stats_cron) = &job_config.stats.cron
+            {
+                info!("Scheduling stats of job '{job_name}' with cron: '{stats_cron}'.");
+                self.schedule(job_name, stats_cron, RunKind::Stats).await;
             }
-        });
+        }
 
-        let main_task = task::spawn({
-            let job_manager_ref = job_manager_ref.clone();
-            let cancellation_token = cancellation_token.clone();
-            async move {
-                info!("Setting up {} jobs...", job_manager_ref.get_jobs().len());
-                for (job_name, job_config) in job_manager_ref.get_jobs() {
-                    info!(
-                        "Scheduling job '{job_name}' with cron: '{}'.",
-                        job_config.cron
-                    );
+        if diff.api_changed {
+            self.stop_server().await;
+            self.start_server(&config.api, cancellation_token);
+        }
 
-                    let job = Job::cron(&format!("0 {}", job_config.cron)).unwrap();
-                    scheduler
-                        .insert(job, {
-                            let jobs_manager_ref = job_manager_ref.clone();
-                            move |_| {
-                                let handle = Handle::current();
-                                let job_name = job_name.clone();
-                                let jobs_manager_ref = jobs_manager_ref.clone();
-                                handle.spawn(async move {
-                                    match jobs_manager_ref.queue_job(job_name.clone()).await {
-                                        Ok(_) => (),
-                                        Err(_) => {
-                                            warn!("Failed to queue job '{job_name}' for execution.")
-                                        }
-                                    };
-                                });
+        info!(
+            "Configuration applied, {} job(s) added, {} changed, {} removed.",
+            diff.added_jobs.len(),
+            diff.changed_jobs.len(),
+            diff.removed_jobs.len()
+        );
+        self.config = Some(config);
+    }
+
+    /// Queues a run of the given kind every time the cron expression fires.
+    async fn schedule(&mut self, job_name: &str, cron: &str, kind: RunKind) {
+        let job = Job::cron(&format!("0 {cron}")).unwrap();
+        let schedule_id = self
+            .scheduler
+            .insert(job, {
+                let job_name = job_name.to_owned();
+                let jobs_manager_ref = self.job_manager_ref.clone();
+                move |_| {
+                    let handle = Handle::current();
+                    let job_name = job_name.clone();
+                    let jobs_manager_ref = jobs_manager_ref.clone();
+                    handle.spawn(async move {
+                        match jobs_manager_ref.queue_job(job_name.clone(), kind).await {
+                            Ok(_) => (),
+                            Err(_) => {
+                                warn!("Failed to queue job '{job_name}' for execution.")
                             }
-                        })
-                        .await;
+                        };
+                    });
+                }
+            })
+            .await;
+        self.schedules
+            .entry(job_name.to_owned())
+            .or_default()
+            .push(schedule_id);
+    }
+
+    async fn unschedule(&mut self, job_name: &str) {
+        for schedule_id in self.schedules.remove(job_name).unwrap_or_default() {
+            self.scheduler.remove(schedule_id).await;
+        }
+    }
+
+    fn start_server(&mut self, config: &ApiConfiguration, cancellation_token: &CancellationToken) {
+        let server_cancellation_token = cancellation_token.child_token();
+        let server_task = task::spawn({
+            let config = config.clone();
+            let job_manager_ref = self.job_manager_ref.clone();
+            let configuration_status = self.configuration_status.clone();
+            let run_history = self.run_history.clone();
+            let stats_history = self.stats_history.clone();
+            let server_cancellation_token = server_cancellation_token.clone();
+            async move {
+                // The jobs keep running without the API, until a configuration change restarts it.
+                if let Err(e) = run_server(
+                    &config,
+                    &job_manager_ref,
+                    &configuration_status,
+                    &run_history,
+                    &stats_history,
+                    &server_cancellation_token,
+                )
+                .await
+                {
+                    error!(
+                        "Failed to run the API server on '{}:{}', the API is unavailable. Error: {e}",
+                        config.host, config.port
+                    );
                 }
-                cancellation_token.cancelled().await;
-                info!("Cancellation triggered, stopping jobs...");
-                // Drops the scheduler...
             }
         });
+        self.server = Some((server_cancellation_token, server_task));
+    }
 
-        main_task.await.unwrap();
-        jobs_task.await.unwrap();
-        cron_task.await.unwrap();
-        server_task.await.unwrap();
+    async fn stop_server(&mut self) {
+        if let Some((server_cancellation_token, server_task)) = self.server.take() {
+            server_cancellation_token.cancel();
+            if let Err(e) = server_task.await {
+                error!("The API server stopped unexpectedly. Error: {e}");
+            }
+        }
     }
 }
@@ -1,24 +1,40 @@
 use crate::jobs::RunnableJob;
-use common::config::BackupJobConfiguration;
-use log::{info, warn};
+use crate::runs::{BackupRun, ManifestWriter};
+use common::config::{
+    BackupJobConfiguration, DiscoveredDrive, MissingSourcePolicy, expand_source, get_job_tag,
+    match_source,
+};
+use log::{debug, error, info, warn};
 use restic_sdk::Restic;
-use restic_sdk::backup::BackupOptions;
+use restic_sdk::backup::{BackupOptions, BackupResult};
 use restic_sdk::errors::ResticError;
+use std::path::PathBuf;
 use sysinfo::Disks;
-use tokio::fs::{canonicalize, try_exists};
+use thiserror::Error;
+use tokio::fs::{canonicalize, remove_file, try_exists};
 use tokio_util::sync::CancellationToken;
 
 pub struct BackupJob {
+    job_id: String,
     config: BackupJobConfiguration,
+    manifest_path: Option<PathBuf>,
 }
 
 impl BackupJob {
-    pub fn new(config: &BackupJobConfiguration) -> Self {
+    pub fn new(job_id: &str, config: &BackupJobConfiguration) -> Self {
         Self {
+            job_id: job_id.to_owned(),
             config: config.clone(),
+            manifest_path: None,
         }
     }
 
+    /// Where to write the manifest of new and modified items, when the configuration has a manifest format.
+    pub fn with_manifest_path(mut self, path: Option<PathBuf>) -> Self {
+        self.manifest_path = path;
+        self
+    }
+
     fn build_backup_options(&self) -> BackupOptions {
         let mut options = BackupOptions::default();
 
@@ -43,6 +59,79 @@ impl BackupJob {
             options = options.with_one_file_system();
         }
 
+        for pattern in &self.config.exclude {
+            options = options.with_exclude(pattern);
+        }
+        for pattern in &self.config.iexclude {
+            options = options.with_iexclude(pattern);
+        }
+        for path in &self.config.exclude_file {
+            options = options.with_exclude_file(path);
+        }
+        for path in &self.config.iexclude_file {
+            options = options.with_iexclude_file(path);
+        }
+        for filename in &self.config.exclude_if_present {
+            options = options.with_exclude_if_present(filename);
+        }
+        if let Some(size) = &self.config.exclude_larger_than {
+            options = options.with_exclude_larger_than(size);
+        }
+        for path in &self.config.files_from {
+            options = options.with_files_from(path);
+        }
+        for path in &self.config.files_from_verbatim {
+            options = options.with_files_from_verbatim(path);
+        }
+        for path in &self.config.files_from_raw {
+            options = options.with_files_from_raw(path);
+        }
+        if let Some(filename) = &self.config.stdin_filename {
+            options = options.with_stdin_filename(filename);
+        }
+
+        // Every snapshot is tagged with its job, so snapshots made by this service can be selected.
+        options = options.with_tag(&get_job_tag(&self.job_id));
+        for tag in &self.config.tags {
+            options = options.with_tag(tag);
+        }
+        if let Some(host) = &self.config.host {
+            options = options.with_host(host);
+        }
+        if let Some(time) = &self.config.time {
+            options = options.with_time(time);
+        }
+        if let Some(group_by) = &self.config.group_by {
+            options = options.with_group_by(group_by);
+        }
+
+        if let Some(parent) = &self.config.parent {
+            options = options.with_parent(parent);
+        }
+        if self.config.force {
+            options = options.with_force();
+        }
+        if self.config.skip_if_unchanged {
+            options = options.with_skip_if_unchanged();
+        }
+        if self.config.no_scan {
+            options = options.with_no_scan();
+        }
+        if let Some(concurrency) = self.config.read_concurrency {
+            options = options.with_read_concurrency(concurrency);
+        }
+        if self.config.with_atime {
+            options = options.with_atime();
+        }
+        if self.config.ignore_inode {
+            options = options.with_ignore_inode();
+        }
+        if self.config.ignore_ctime {
+            options = options.with_ignore_ctime();
+        }
+
+        options = options.with_max_errors(self.config.max_errors);
+
         // Additional flags for any custom options not covered above
         for flag in &self.config.additional_flags {
             options = options.with_flag(flag);
@@ -51,49 +140,161 @@ impl BackupJob {
         options
     }
 
-    async fn get_backup_sources(&self) -> Vec<String> {
+    async fn get_backup_sources(&self) -> Result<Vec<String>, BackupJobError> {
         let mut sources = Vec::new();
 
         // Source explicit sources.
         for source in &self.config.sources {
-            if try_exists(source).await.unwrap_or_else(|_| {
-                warn!("Failed to check existence of source path '{source}', it will be ignored.");
-                false
-            }) {
-                match canonicalize(source).await {
-                    Ok(_) => {
-                        sources.push(source.clone());
+            let resolved = Self::resolve_source(source.path()).await;
+            if resolved.is_empty() {
+                let path = source.path();
+                match self.config.missing_sources {
+                    _ if source.is_required() => {
+                        return Err(BackupJobError::MissingRequiredSource(path.to_owned()));
                     }
-                    Err(_) => {
-                        warn!("Failed to canonicalize source path '{source}', it will be ignored.");
+                    MissingSourcePolicy::Fail => {
+                        return Err(BackupJobError::MissingSource(path.to_owned()));
                     }
-                };
+                    MissingSourcePolicy::Warn => {
+                        warn!(
+                            "Source '{path}' does not exist or matches nothing, it will be ignored."
+                        )
+                    }
+                    MissingSourcePolicy::Ignore => {
+                        debug!(
+                            "Source '{path}' does not exist or matches nothing, it will be ignored."
+                        )
+                    }
+                }
+            }
+            for path in resolved {
+                if !sources.contains(&path) {
+                    sources.push(path);
+                }
             }
         }
 
         // Source fixed drives if configured.
         if self.config.source_fixed_drives {
-            let disks = Disks::new_with_refreshed_list();
-            for disk in disks.list() {
-                if !disk.is_removable() {
-                    if let Some(mount_point) = disk.mount_point().to_str() {
-                        sources.push(mount_point.to_owned());
-                    } else {
-                        warn!("Failed to convert disk '{disk:?}' to string, it will be ignored.");
-                    }
+            for mount_point in self.get_fixed_drives() {
+                if !sources.contains(&mount_point) {
+                    sources.push(mount_point);
                 }
             }
         }
 
-        sources
+        Ok(sources)
     }
-}
 
-impl RunnableJob for BackupJob {
-    async fn run(
+    /// Discovers the drives of this machine, then filters them, logging why drives are skipped.
+    fn get_fixed_drives(&self) -> Vec<String> {
+        let disks = Disks::new_with_refreshed_list();
+        let mut report = Vec::new();
+        let mut selected = Vec::new();
+
+        for disk in disks.list() {
+            let Some(mount_point) = disk.mount_point().to_str() else {
+                warn!("Failed to convert disk '{disk:?}' to string, it will be ignored.");
+                continue;
+            };
+
+            let drive = DiscoveredDrive {
+                mount_point: mount_point.to_owned(),
+                file_system: disk.file_system().to_string_lossy().into_owned(),
+                total_space: disk.total_space(),
+                is_removable: disk.is_removable(),
+            };
+            match self.config.fixed_drives.select(&drive) {
+                Ok(_) => {
+                    report.push(format!("'{mount_point}' ({}): selected", drive.file_system));
+                    selected.push(drive.mount_point);
+                }
+                Err(reason) => {
+                    report.push(format!(
+                        "'{mount_point}' ({}): skipped, {reason}",
+                        drive.file_system
+                    ));
+                }
+            }
+        }
+
+        info!(
+            "Discovered {} drive(s), selected {}: [{}].",
+            report.len(),
+            selected.len(),
+            report.join(", ")
+        );
+        selected
+    }
+
+    /// Whether restic reads the files to backup from a file, so no sources are needed.
+    fn has_files_from(&self) -> bool {
+        !self.config.files_from.is_empty()
+            || !self.config.files_from_verbatim.is_empty()
+            || !self.config.files_from_raw.is_empty()
+    }
+
+    /// Backs up the output of the configured command, failing when the command exits with a non-zero code.
+    async fn run_stdin_command(
         &self,
         client: &Restic,
         cancellation_token: &CancellationToken,
+    ) -> Result<BackupRun, BackupJobError> {
+        Self::ensure_repository(client, cancellation_token).await?;
+
+        let command = &self.config.stdin_command;
+        info!("Will backup the output of {command:?}...");
+        let backup_result = client
+            .backup_from_command(command, self.build_backup_options(), cancellation_token)
+            .await
+            .map_err(|e| match e {
+                // Restic does not create a snapshot when the command fails.
+                ResticError::StdinCommandFailed(_) => {
+                    BackupJobError::StdinCommandFailed(command[0].clone(), e)
+                }
+                e => BackupJobError::Restic(e),
+            })?;
+
+        Self::log_result(&backup_result);
+
+        Ok(BackupRun {
+            stdin_command: command.clone(),
+            unchanged: backup_result.is_unchanged(),
+            snapshot_id: backup_result.summary.snapshot_id,
+            failed_to_read_some_data: backup_result.failed_to_read_some_data,
+            error_count: backup_result.error_count,
+            errors: backup_result.errors.into_iter().map(Into::into).collect(),
+            ..BackupRun::default()
+        })
+    }
+
+    async fn create_manifest(&self) -> Option<ManifestWriter> {
+        let format = self.config.manifest?;
+        let path = self.manifest_path.as_ref()?;
+        match ManifestWriter::create(path, format).await {
+            Ok(manifest) => Some(manifest),
+            Err(e) => {
+                warn!(
+                    "Failed to create manifest '{}', the backup runs without it. Error: {e}",
+                    path.display()
+                );
+                None
+            }
+        }
+    }
+
+    fn log_result(backup_result: &BackupResult) {
+        match backup_result.is_unchanged() {
+            true => info!(
+                "Backup completed without changes, no snapshot was created. Summary {backup_result:?}"
+            ),
+            false => info!("Backup completed successfully with summary {backup_result:?}"),
+        }
+    }
+
+    async fn ensure_repository(
+        client: &Restic,
+        cancellation_token: &CancellationToken,
     ) -> Result<(), ResticError> {
         if !client.can_open(cancellation_token).await? {
             info!("Restic repository cannot be opened, assuming it does not exist.");
@@ -103,18 +304,129 @@ impl RunnableJob for BackupJob {
                 result.id
             );
         }
+        Ok(())
+    }
+
+    /// Expands the source, then returns the existing paths it refers to.
+    async fn resolve_source(source: &str) -> Vec<String> {
+        let expanded = expand_source(source);
+        if expanded != source {
+            debug!("Source '{source}' expanded to '{expanded}'.");
+        }
+
+        let candidates = match_source(&expanded).unwrap_or_else(|e| {
+            warn!("Source '{source}' is not a valid pattern. Error: {e}");
+            Vec::new()
+        });
+
+        let mut resolved = Vec::new();
+        for candidate in candidates {
+            if try_exists(&candidate).await.unwrap_or_else(|_| {
+                warn!(
+                    "Failed to check existence of source path '{candidate}', it will be ignored."
+                );
+                false
+            }) {
+                match canonicalize(&candidate).await {
+                    Ok(_) => {
+                        resolved.push(candidate);
+                    }
+                    Err(_) => {
+                        warn!(
+                            "Failed to canonicalize source path '{candidate}', it will be ignored."
+                        );
+                    }
+                };
+            }
+        }
+        resolved
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum BackupJobError {
+    #[error("Required source '{0}' does not exist or matches nothing")]
+    MissingRequiredSource(String),
+    #[error("Source '{0}' does not exist or matches nothing")]
+    MissingSource(String),
+    #[error("No sources to backup, every source is missing")]
+    NoSources,
+    #[error("Command '{0}' failed: {1}")]
+    StdinCommandFailed(String, ResticError),
+    #[error(transparent)]
+    Restic(#[from] ResticError),
+}
+
+impl RunnableJob for BackupJob {
+    type Output = BackupRun;
+    type Error = BackupJobError;
+
+    async fn run(
+        &self,
+        client: &Restic,
+        cancellation_token: &CancellationToken,
+    ) -> Result<BackupRun, BackupJobError> {
+        if !self.config.stdin_command.is_empty() {
+            return self.run_stdin_command(client, cancellation_token).await;
+        }
+
+        let sources = self.get_backup_sources().await?;
+        if sources.is_empty() && !self.has_files_from() {
+            error!(
+                "No sources to backup, check that the configured sources exist or enable source_fixed_drives."
+            );
+            return Err(BackupJobError::NoSources);
+        }
 
-        let backup_options = self.build_backup_options();
-        let sources = self.get_backup_sources().await;
+        Self::ensure_repository(client, cancellation_token).await?;
+
+        let mut backup_options = self.build_backup_options();
+        let manifest = self.create_manifest().await;
+        if manifest.is_some() {
+            // Restic only reports every item from this level.
+            backup_options = backup_options.with_verbose_level(2);
+        }
 
         info!("Will backup [{:?}]...", sources.join(", "));
         let backup_result = client
-            .backup(sources, backup_options, cancellation_token)
-            .await?;
+            .backup_with_items(
+                &sources,
+                backup_options,
+                |status| {
+                    if let Some(manifest) = &manifest {
+                        manifest.write(&status);
+                    }
+                },
+                cancellation_token,
+            )
+            .await;
+        let manifest = match manifest {
+            Some(manifest) => manifest.finish().await,
+            None => None,
+        };
+        let backup_result = match backup_result {
+            Ok(backup_result) => backup_result,
+            Err(e) => {
+                // The run has no backup to keep the manifest with.
+                if let Some(path) = &manifest {
+                    _ = remove_file(path).await;
+                }
+                return Err(e.into());
+            }
+        };
 
-        info!("Backup completed successfully with summary {backup_result:?}");
+        Self::log_result(&backup_result);
 
-        Ok(())
+        Ok(BackupRun {
+            sources,
+            manifest,
+            unchanged: backup_result.is_unchanged(),
+            snapshot_id: backup_result.summary.snapshot_id,
+            failed_to_read_some_data: backup_result.failed_to_read_some_data,
+            error_count: backup_result.error_count,
+            errors: backup_result.errors.into_iter().map(Into::into).collect(),
+            ..BackupRun::default()
+        })
     }
 
     fn get_job_name(&self) -> &str {
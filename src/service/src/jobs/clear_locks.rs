@@ -18,6 +18,9 @@ impl ClearLocksJob {
 }
 
 impl RunnableJob for ClearLocksJob {
+    type Output = ();
+    type Error = ResticError;
+
     async fn run(
         &self,
         client: &Restic,
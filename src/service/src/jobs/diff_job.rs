@@ -0,0 +1,67 @@
+use crate::jobs::RunnableJob;
+use log::info;
+use restic_sdk::Restic;
+use restic_sdk::diff::DiffOptions;
+use restic_sdk::errors::ResticError;
+use restic_sdk::messages::DiffStatistics;
+use restic_sdk::snapshots::SnapshotsOptions;
+use tokio_util::sync::CancellationToken;
+
+/// Compares a snapshot with its parent, the snapshot the backup that created it started from.
+pub struct DiffJob {
+    snapshot_id: String,
+}
+
+impl DiffJob {
+    pub fn new(snapshot_id: &str) -> Self {
+        Self {
+            snapshot_id: snapshot_id.to_owned(),
+        }
+    }
+}
+
+impl RunnableJob for DiffJob {
+    /// `None` when the snapshot has no parent, e.g. the first snapshot of the job.
+    type Output = Option<DiffStatistics>;
+    type Error = ResticError;
+
+    async fn run(
+        &self,
+        client: &Restic,
+        cancellation_token: &CancellationToken,
+    ) -> Result<Option<DiffStatistics>, ResticError> {
+        let snapshots = client
+            .snapshots(
+                SnapshotsOptions::new().snapshot(&self.snapshot_id),
+                cancellation_token,
+            )
+            .await?;
+        let Some(parent) = snapshots.into_iter().next().and_then(|x| x.parent) else {
+            info!(
+                "Snapshot {} has no parent, there is nothing to compare it with.",
+                self.snapshot_id
+            );
+            return Ok(None);
+        };
+
+        let statistics = client
+            .diff(
+                &parent,
+                &self.snapshot_id,
+                DiffOptions::new(),
+                |_| {},
+                cancellation_token,
+            )
+            .await?;
+        info!(
+            "Since snapshot {parent}: {} file(s) added, {} removed and {} changed.",
+            statistics.added.files, statistics.removed.files, statistics.changed_files
+        );
+
+        Ok(Some(statistics))
+    }
+
+    fn get_job_name(&self) -> &str {
+        "Diff"
+    }
+}
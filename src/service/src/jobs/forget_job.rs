@@ -1,138 +1,160 @@
 use crate::jobs::RunnableJob;
+use crate::runs::ForgetRun;
 use common::config::ForgetConfiguration;
 use log::info;
 use restic_sdk::Restic;
 use restic_sdk::errors::ResticError;
-use restic_sdk::forget::ForgetOptions;
+use restic_sdk::forget::{ForgetOptions, ForgetResult};
+use restic_sdk::retention::{RetentionError, RetentionPolicy};
+use std::str::FromStr;
 use tokio_util::sync::CancellationToken;
 
 pub struct ForgetJob {
     config: ForgetConfiguration,
+    policy: RetentionPolicy,
+    /// Everything but the behavior options, which the preview overrides.
+    options: ForgetOptions,
 }
 
 impl ForgetJob {
-    pub fn new(config: &ForgetConfiguration) -> Self {
-        Self {
-            config: config.clone(),
+    /// Parses the policy and sizes of the configuration, an invalid value is an error, as is an empty policy when
+    /// forget is enabled.
+    pub fn new(job_id: &str, config: &ForgetConfiguration) -> Result<Self, RetentionError> {
+        let mut policy = RetentionPolicy {
+            keep_last: config.keep_last,
+            keep_hourly: config.keep_hourly,
+            keep_daily: config.keep_daily,
+            keep_weekly: config.keep_weekly,
+            keep_monthly: config.keep_monthly,
+            keep_yearly: config.keep_yearly,
+            keep_within: parse_option(&config.keep_within)?,
+            keep_within_hourly: parse_option(&config.keep_within_hourly)?,
+            keep_within_daily: parse_option(&config.keep_within_daily)?,
+            keep_within_weekly: parse_option(&config.keep_within_weekly)?,
+            keep_within_monthly: parse_option(&config.keep_within_monthly)?,
+            keep_within_yearly: parse_option(&config.keep_within_yearly)?,
+            keep_tags: config.keep_tag.iter().cloned().collect(),
+            unsafe_allow_remove_all: config.unsafe_allow_remove_all,
+        };
+        if config.enabled {
+            policy.validate()?;
+        }
+        // Pinned snapshots are always kept, yet pinning alone is no policy, so it is added after validating.
+        if !config.pin_tag.is_empty() {
+            policy.keep_tags.push(config.pin_tag.clone());
         }
-    }
-
-    fn get_forget_and_prune_options(&self) -> ForgetOptions {
-        let mut options = ForgetOptions::default();
 
-        // Retention policy options
-        if let Some(group_by) = &self.config.group_by {
+        let mut options = ForgetOptions::default().policy(&policy);
+        if let Some(group_by) = &config.group_by {
             options = options.group_by(group_by);
         }
-        if let Some(keep_last) = self.config.keep_last {
-            options = options.keep_last(keep_last);
-        }
-        if let Some(keep_hourly) = self.config.keep_hourly {
-            options = options.keep_hourly(keep_hourly);
-        }
-        if let Some(keep_daily) = self.config.keep_daily {
-            options = options.keep_daily(keep_daily);
-        }
-        if let Some(keep_weekly) = self.config.keep_weekly {
-            options = options.keep_weekly(keep_weekly);
-        }
-        if let Some(keep_monthly) = self.config.keep_monthly {
-            options = options.keep_monthly(keep_monthly);
-        }
-        if let Some(keep_yearly) = self.config.keep_yearly {
-            options = options.keep_yearly(keep_yearly);
-        }
-        if let Some(keep_within) = &self.config.keep_within {
-            options = options.keep_within(keep_within);
-        }
-        if let Some(keep_within_hourly) = &self.config.keep_within_hourly {
-            options = options.keep_within_hourly(*keep_within_hourly);
-        }
-        if let Some(keep_within_daily) = &self.config.keep_within_daily {
-            options = options.keep_within_daily(*keep_within_daily);
-        }
-        if let Some(keep_within_weekly) = &self.config.keep_within_weekly {
-            options = options.keep_within_weekly(*keep_within_weekly);
-        }
-        if let Some(keep_within_monthly) = &self.config.keep_within_monthly {
-            options = options.keep_within_monthly(*keep_within_monthly);
-        }
-        if let Some(keep_within_yearly) = &self.config.keep_within_yearly {
-            options = options.keep_within_yearly(*keep_within_yearly);
-        }
-        if let Some(keep_tag) = &self.config.keep_tag {
-            options = options.keep_tag(keep_tag);
-        }
 
         // Filtering options
-        if let Some(host) = &self.config.host {
+        if let Some(host) = &config.host {
             options = options.host(host);
         }
-        if let Some(tag) = &self.config.tag {
-            options = options.tag(tag);
+        for tag in config.get_tag_filters(job_id) {
+            options = options.tag(&tag);
         }
-        if let Some(path) = &self.config.path {
+        if let Some(path) = &config.path {
             options = options.path(path);
         }
 
-        // Behavior options
-        if self.config.unsafe_allow_remove_all {
-            options = options.unsafe_allow_remove_all();
-        }
-        if self.config.compact {
+        if config.compact {
             options = options.compact();
         }
-        if self.config.dry_run {
-            options = options.dry_run();
-        }
-        if self.config.prune {
-            options = options.prune();
-        }
 
         // Prune-specific options
-        if let Some(max_unused) = &self.config.max_unused {
+        if let Some(max_unused) = parse_option(&config.max_unused)? {
             options = options.max_unused(max_unused);
         }
-        if let Some(max_repack_size) = &self.config.max_repack_size {
+        if let Some(max_repack_size) = parse_option(&config.max_repack_size)? {
             options = options.max_repack_size(max_repack_size);
         }
-        if self.config.repack_cacheable_only {
+        if config.repack_cacheable_only {
             options = options.repack_cacheable_only();
         }
-        if self.config.repack_small {
+        if config.repack_small {
             options = options.repack_small();
         }
-        if self.config.repack_uncompressed {
+        if config.repack_uncompressed {
             options = options.repack_uncompressed();
         }
-        if let Some(repack_smaller_than) = &self.config.repack_smaller_than {
+        if let Some(repack_smaller_than) = parse_option(&config.repack_smaller_than)? {
             options = options.repack_smaller_than(repack_smaller_than);
         }
 
         // Additional flags for any custom options not covered above
-        for flag in &self.config.additional_flags {
+        for flag in &config.additional_flags {
             options = options.with_flag(flag);
         }
 
+        Ok(Self {
+            config: config.clone(),
+            policy,
+            options,
+        })
+    }
+
+    pub fn policy(&self) -> &RetentionPolicy {
+        &self.policy
+    }
+
+    fn get_forget_and_prune_options(&self) -> ForgetOptions {
+        let mut options = self.options.clone();
+
+        // Behavior options
+        if self.config.dry_run {
+            options = options.dry_run();
+        }
+        if self.config.prune {
+            options = options.prune();
+        }
+
         options
     }
+
+    /// Runs forget as a dry run without prune, returning what the policy would keep and remove.
+    pub async fn preview(
+        &self,
+        client: &Restic,
+        cancellation_token: &CancellationToken,
+    ) -> Result<ForgetResult, ResticError> {
+        client
+            .forget(self.options.clone().dry_run(), cancellation_token)
+            .await
+    }
+}
+
+/// Parses an optional value of the configuration.
+pub(crate) fn parse_option<T: FromStr>(value: &Option<String>) -> Result<Option<T>, T::Err> {
+    value.as_deref().map(str::parse).transpose()
 }
 
 impl RunnableJob for ForgetJob {
+    type Output = Option<ForgetRun>;
+    type Error = ResticError;
+
     async fn run(
         &self,
         client: &Restic,
         cancellation_token: &CancellationToken,
-    ) -> Result<(), ResticError> {
+    ) -> Result<Option<ForgetRun>, ResticError> {
         if !self.config.enabled {
             info!("Forget and prune is disabled by configuration.");
-            return Ok(());
+            return Ok(None);
         }
 
+        info!("Forget with policy: {}.", self.policy);
         let forget_options = self.get_forget_and_prune_options();
-        client.forget(forget_options, cancellation_token).await?;
+        let result = client.forget(forget_options, cancellation_token).await?;
 
-        Ok(())
+        info!(
+            "Forget kept {} snapshot(s) and removed {}.",
+            result.kept().count(),
+            result.removed().count()
+        );
+        Ok(Some(result.into()))
     }
 
     fn get_job_name(&self) -> &str {
@@ -1,44 +1,97 @@
-use common::config::{ResticJob, ServiceConfiguration};
+use crate::runs::RunKind;
+use common::config::ResticJob;
 use log::info;
+use std::collections::HashMap;
+use std::sync::RwLock;
 use thiserror::Error;
 use tokio::sync::mpsc::Sender;
 use tokio::sync::mpsc::error::SendError;
+use tokio_util::sync::CancellationToken;
 
 pub struct JobManager {
-    config: ServiceConfiguration,
-    sender: Sender<(String, ResticJob)>,
+    jobs: RwLock<HashMap<String, ManagedJob>>,
+    sender: Sender<QueuedJob>,
+    cancellation_token: CancellationToken,
+}
+
+/// A configured job, the token is cancelled when the job is changed or removed.
+#[derive(Clone)]
+struct ManagedJob {
+    config: ResticJob,
+    cancellation_token: CancellationToken,
+}
+
+/// A job waiting in the queue, pinned to the configuration it was queued with.
+#[derive(Debug)]
+pub struct QueuedJob {
+    pub job_id: String,
+    pub kind: RunKind,
+    pub config: ResticJob,
+    pub cancellation_token: CancellationToken,
 }
 
 impl JobManager {
-    pub fn new(config: ServiceConfiguration, sender: Sender<(String, ResticJob)>) -> Self {
-        Self { config, sender }
+    pub fn new(sender: Sender<QueuedJob>, cancellation_token: &CancellationToken) -> Self {
+        Self {
+            jobs: RwLock::new(HashMap::new()),
+            sender,
+            cancellation_token: cancellation_token.clone(),
+        }
     }
 
-    pub fn get_job_names(&self) -> impl Iterator<Item = &String> {
-        self.config.jobs.keys()
+    pub fn get_job_names(&self) -> Vec<String> {
+        self.jobs.read().unwrap().keys().cloned().collect()
     }
 
     pub fn get_jobs(&self) -> Vec<(String, ResticJob)> {
-        self.config
-            .jobs
+        self.jobs
+            .read()
+            .unwrap()
             .iter()
-            .map(|(job_id, job_config)| (job_id.clone(), job_config.clone()))
+            .map(|(job_id, job)| (job_id.clone(), job.config.clone()))
             .collect()
     }
 
-    pub async fn queue_job(&self, job_id: impl Into<String>) -> Result<(), QueueJobError> {
+    /// Adds or replaces a job, any running or queued run of the previous configuration is canceled.
+    pub fn upsert_job(&self, job_id: impl Into<String>, config: ResticJob) {
+        let job = ManagedJob {
+            config,
+            cancellation_token: self.cancellation_token.child_token(),
+        };
+        if let Some(previous) = self.jobs.write().unwrap().insert(job_id.into(), job) {
+            previous.cancellation_token.cancel();
+        }
+    }
+
+    /// Removes a job, any running or queued run is canceled.
+    pub fn remove_job(&self, job_id: &str) {
+        if let Some(previous) = self.jobs.write().unwrap().remove(job_id) {
+            previous.cancellation_token.cancel();
+        }
+    }
+
+    pub async fn queue_job(
+        &self,
+        job_id: impl Into<String>,
+        kind: RunKind,
+    ) -> Result<(), QueueJobError> {
         let job_id = job_id.into();
 
-        let Some(job) = self.config.jobs.get(&job_id) else {
+        let Some(job) = self.jobs.read().unwrap().get(&job_id).cloned() else {
             return Err(QueueJobError::JobNotFound(job_id));
         };
 
         self.sender
-            .send((job_id.clone(), job.clone()))
+            .send(QueuedJob {
+                job_id: job_id.clone(),
+                kind,
+                config: job.config,
+                cancellation_token: job.cancellation_token,
+            })
             .await
             .map_err(QueueJobError::QueueSendError)?;
 
-        info!("Job '{job_id}' is queued.");
+        info!("Job '{job_id}' is queued ({kind:?}).");
 
         Ok(())
     }
@@ -50,5 +103,5 @@ pub enum QueueJobError {
     #[error("job {0} not found")]
     JobNotFound(String),
     #[error("failed to send job to queue")]
-    QueueSendError(SendError<(String, ResticJob)>),
+    QueueSendError(SendError<QueuedJob>),
 }
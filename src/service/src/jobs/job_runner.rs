@@ -1,48 +1,178 @@
 use super::forget_job::ForgetJob;
-use crate::jobs::backup_job::BackupJob;
+use crate::jobs::backup_job::{BackupJob, BackupJobError};
 use crate::jobs::clear_locks::ClearLocksJob;
-use common::config::ResticJob;
+use crate::jobs::diff_job::DiffJob;
+use crate::jobs::prune_job::PruneJob;
+use crate::jobs::stats_job::StatsJob;
+use crate::runs::{JobRun, RunHistory, RunKind, StatsHistory};
+use common::config::{ConfigurationError, PasswordSource, ResticJob, interpolate_env};
 use log::{info, warn};
-use restic_sdk::errors::ResticError;
 use restic_sdk::{Restic, ResticConfig};
+use std::env;
+use std::fmt::{Debug, Display};
+use std::path::PathBuf;
 use tokio::time::Instant;
 use tokio_util::sync::CancellationToken;
 
 pub struct JobRunner {}
 
 impl JobRunner {
-    pub async fn run(job_config: &ResticJob, cancellation_token: &CancellationToken) {
-        let client = Self::build_restic_client(job_config);
-
-        Self::run_job(
-            &client,
-            ClearLocksJob::new(&job_config.clear_locks),
-            cancellation_token,
-        )
-        .await;
-
-        Self::run_job(
-            &client,
-            BackupJob::new(&job_config.backup),
-            cancellation_token,
-        )
-        .await;
-
-        Self::run_job(
-            &client,
-            ForgetJob::new(&job_config.forget_and_purge),
-            cancellation_token,
-        )
-        .await;
+    pub async fn run(
+        job_id: &str,
+        job_config: &ResticJob,
+        kind: RunKind,
+        run_history: &RunHistory,
+        stats_history: &StatsHistory,
+        cancellation_token: &CancellationToken,
+    ) {
+        let mut run = run_history.start(job_id, kind);
+        match kind {
+            RunKind::Backup => {
+                let manifest_path = job_config
+                    .backup
+                    .manifest
+                    .map(|format| run_history.get_manifest_path(&run, format));
+                Self::run_steps(&mut run, job_config, manifest_path, cancellation_token).await;
+            }
+            RunKind::Prune => Self::run_prune(&mut run, job_config, cancellation_token).await,
+            RunKind::Stats => {
+                Self::run_stats(&mut run, job_config, stats_history, cancellation_token).await
+            }
+        }
+        run.finish(cancellation_token.is_cancelled());
+
+        info!(
+            "Run {} of job '{job_id}' finished with outcome {:?}.",
+            run.run_id, run.outcome
+        );
+        run_history.finish(run).await;
     }
 
-    async fn run_job(
-        client: &Restic,
-        job: impl RunnableJob,
+    async fn run_steps(
+        run: &mut JobRun,
+        job_config: &ResticJob,
+        manifest_path: Option<PathBuf>,
+        cancellation_token: &CancellationToken,
+    ) {
+        let client = match Self::build_restic_client(job_config) {
+            Ok(client) => client,
+            Err(e) => {
+                warn!("Failed to configure restic, the job will not run. Error: {e}");
+                run.add_failure(format!("Failed to configure restic: {e}"));
+                return;
+            }
+        };
+
+        let clear_locks = ClearLocksJob::new(&job_config.clear_locks);
+        if let Some(Err(e)) = Self::run_job(&client, &clear_locks, cancellation_token).await {
+            run.add_failure(format!("{}: {e}", clear_locks.get_job_name()));
+        }
+
+        let backup =
+            BackupJob::new(&run.job_id, &job_config.backup).with_manifest_path(manifest_path);
+        match Self::run_job(&client, &backup, cancellation_token).await {
+            Some(Ok(backup_run)) => run.backup = Some(backup_run),
+            Some(Err(e @ BackupJobError::NoSources)) => {
+                run.add_no_sources_failure(format!("{}: {e}", backup.get_job_name()))
+            }
+            Some(Err(e)) => run.add_failure(format!("{}: {e}", backup.get_job_name())),
+            None => {}
+        }
+
+        if job_config.backup.diff_with_parent
+            && let Some(snapshot_id) = run.backup.as_ref().and_then(|x| x.snapshot_id.clone())
+        {
+            let diff = DiffJob::new(&snapshot_id);
+            match Self::run_job(&client, &diff, cancellation_token).await {
+                Some(Ok(statistics)) => {
+                    if let Some(backup) = &mut run.backup {
+                        backup.diff = statistics;
+                    }
+                }
+                // The snapshot was created, so only the statistics are missing.
+                Some(Err(e)) => run.add_warning(format!("{}: {e}", diff.get_job_name())),
+                None => {}
+            }
+        }
+
+        let forget = match ForgetJob::new(&run.job_id, &job_config.forget_and_purge) {
+            Ok(forget) => forget,
+            Err(e) => {
+                run.add_failure(format!("Forget and Prune: {e}"));
+                return;
+            }
+        };
+        match Self::run_job(&client, &forget, cancellation_token).await {
+            Some(Ok(forget_run)) => run.forget = forget_run,
+            Some(Err(e)) => run.add_failure(format!("{}: {e}", forget.get_job_name())),
+            None => {}
+        }
+    }
+
+    async fn run_prune(
+        run: &mut JobRun,
+        job_config: &ResticJob,
         cancellation_token: &CancellationToken,
     ) {
+        let client = match Self::build_restic_client(job_config) {
+            Ok(client) => client,
+            Err(e) => {
+                warn!("Failed to configure restic, the prune will not run. Error: {e}");
+                run.add_failure(format!("Failed to configure restic: {e}"));
+                return;
+            }
+        };
+
+        let clear_locks = ClearLocksJob::new(&job_config.clear_locks);
+        if let Some(Err(e)) = Self::run_job(&client, &clear_locks, cancellation_token).await {
+            run.add_failure(format!("{}: {e}", clear_locks.get_job_name()));
+        }
+
+        let prune = match PruneJob::new(&job_config.prune) {
+            Ok(prune) => prune,
+            Err(e) => {
+                run.add_failure(format!("Prune: {e}"));
+                return;
+            }
+        };
+        match Self::run_job(&client, &prune, cancellation_token).await {
+            Some(Ok(stats)) => run.prune = stats,
+            Some(Err(e)) => run.add_failure(format!("{}: {e}", prune.get_job_name())),
+            None => {}
+        }
+    }
+
+    async fn run_stats(
+        run: &mut JobRun,
+        job_config: &ResticJob,
+        stats_history: &StatsHistory,
+        cancellation_token: &CancellationToken,
+    ) {
+        let client = match Self::build_restic_client(job_config) {
+            Ok(client) => client,
+            Err(e) => {
+                warn!("Failed to configure restic, the stats will not run. Error: {e}");
+                run.add_failure(format!("Failed to configure restic: {e}"));
+                return;
+            }
+        };
+
+        let stats = StatsJob::new(&run.job_id, &job_config.stats);
+        match Self::run_job(&client, &stats, cancellation_token).await {
+            Some(Ok(samples)) => stats_history.add(samples).await,
+            Some(Err(e)) => run.add_failure(format!("{}: {e}", stats.get_job_name())),
+            None => {}
+        }
+    }
+
+    /// Runs the step unless the job is cancelled, `None` when it did not run.
+    async fn run_job<J: RunnableJob>(
+        client: &Restic,
+        job: &J,
+        cancellation_token: &CancellationToken,
+    ) -> Option<Result<J::Output, J::Error>> {
         if cancellation_token.is_cancelled() {
-            return;
+            return None;
         }
 
         let job_name = job.get_job_name();
@@ -51,9 +181,8 @@ impl JobRunner {
         info!("Running {job_name}...");
         let result = job.run(client, cancellation_token).await;
 
-        match result {
+        match &result {
             Ok(_) => {
-                start.elapsed();
                 info!(
                     "{job_name} completed successfully in {:?}.",
                     start.elapsed()
@@ -63,27 +192,48 @@ impl JobRunner {
                 warn!("{job_name} failed with error: {e:?}");
             }
         }
+
+        Some(result)
     }
 
-    fn build_restic_client(job_config: &ResticJob) -> Restic {
-        let mut restic_config = ResticConfig::default()
-            .with_repository(&job_config.repository)
-            .with_password(&job_config.password);
+    /// Creates a client for the repository of the job, with its environment and password.
+    pub fn build_restic_client(job_config: &ResticJob) -> Result<Restic, ConfigurationError> {
+        let mut restic_config = ResticConfig::default().with_repository(&job_config.repository);
 
+        // Environment goes first, so the explicit password source always wins.
         for (env_name, env_value) in &job_config.environment {
-            restic_config = restic_config.with_env_var(env_name, env_value);
+            restic_config = restic_config.with_env_var(env_name, &interpolate_env(env_value)?);
         }
 
-        Restic::new().with_config(restic_config)
+        restic_config = match job_config.password_source() {
+            Some(PasswordSource::Password(password)) => restic_config.with_password(&password),
+            Some(PasswordSource::File(path)) => restic_config.with_password_file(&path),
+            Some(PasswordSource::Command(command)) => restic_config.with_password_command(&command),
+            Some(PasswordSource::Env(name)) => {
+                let password = env::var(&name)
+                    .map_err(|_| ConfigurationError::MissingEnvironmentVariable(name))?;
+                restic_config.with_password(&password)
+            }
+            None => {
+                return Err(ConfigurationError::InvalidConfiguration(
+                    "exactly one password source is required".to_owned(),
+                ));
+            }
+        };
+
+        Ok(Restic::new().with_config(restic_config))
     }
 }
 
 pub trait RunnableJob {
+    type Output;
+    type Error: Debug + Display;
+
     async fn run(
         &self,
         client: &Restic,
         cancellation_token: &CancellationToken,
-    ) -> Result<(), ResticError>;
+    ) -> Result<Self::Output, Self::Error>;
 
     fn get_job_name(&self) -> &str;
 }
@@ -1,8 +1,13 @@
 mod backup_job;
 mod clear_locks;
+mod diff_job;
 mod forget_job;
 mod job_manager;
 mod job_runner;
+mod prune_job;
+mod stats_job;
 
+pub use forget_job::*;
 pub use job_manager::*;
 pub use job_runner::*;
+pub use prune_job::*;
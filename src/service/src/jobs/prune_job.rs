@@ -0,0 +1,69 @@
+use crate::jobs::RunnableJob;
+use crate::jobs::forget_job::parse_option;
+use common::config::PruneJobConfiguration;
+use restic_sdk::Restic;
+use restic_sdk::errors::ResticError;
+use restic_sdk::messages::PruneStats;
+use restic_sdk::prune::PruneOptions;
+use restic_sdk::retention::RetentionError;
+use tokio_util::sync::CancellationToken;
+
+pub struct PruneJob {
+    options: PruneOptions,
+}
+
+impl PruneJob {
+    /// Parses the sizes of the configuration, an invalid size is an error.
+    pub fn new(config: &PruneJobConfiguration) -> Result<Self, RetentionError> {
+        let mut options = PruneOptions::default();
+
+        if config.dry_run {
+            options = options.dry_run();
+        }
+        if let Some(max_unused) = parse_option(&config.max_unused)? {
+            options = options.max_unused(max_unused);
+        }
+        if let Some(max_repack_size) = parse_option(&config.max_repack_size)? {
+            options = options.max_repack_size(max_repack_size);
+        }
+        if config.repack_cacheable_only {
+            options = options.repack_cacheable_only();
+        }
+        if config.repack_small {
+            options = options.repack_small();
+        }
+        if config.repack_uncompressed {
+            options = options.repack_uncompressed();
+        }
+        if let Some(repack_smaller_than) = parse_option(&config.repack_smaller_than)? {
+            options = options.repack_smaller_than(repack_smaller_than);
+        }
+
+        // Additional flags for any custom options not covered above
+        for flag in &config.additional_flags {
+            options = options.with_flag(flag);
+        }
+
+        Ok(Self { options })
+    }
+}
+
+impl RunnableJob for PruneJob {
+    type Output = Option<PruneStats>;
+    type Error = ResticError;
+
+    async fn run(
+        &self,
+        client: &Restic,
+        cancellation_token: &CancellationToken,
+    ) -> Result<Option<PruneStats>, ResticError> {
+        let result = client
+            .prune(self.options.clone(), cancellation_token)
+            .await?;
+        Ok(result.stats)
+    }
+
+    fn get_job_name(&self) -> &str {
+        "Prune"
+    }
+}
@@ -0,0 +1,89 @@
+use crate::jobs::RunnableJob;
+use crate::runs::StatsSample;
+use chrono::Local;
+use common::config::{StatsJobConfiguration, StatsMode};
+use log::info;
+use restic_sdk::Restic;
+use restic_sdk::errors::ResticError;
+use restic_sdk::stats::{self, StatsOptions};
+use tokio_util::sync::CancellationToken;
+
+pub struct StatsJob {
+    job_id: String,
+    config: StatsJobConfiguration,
+}
+
+impl StatsJob {
+    pub fn new(job_id: &str, config: &StatsJobConfiguration) -> Self {
+        Self {
+            job_id: job_id.to_owned(),
+            config: config.clone(),
+        }
+    }
+
+    /// Each configured host, or the whole repository when there are none.
+    fn get_hosts(&self) -> Vec<Option<&str>> {
+        match self.config.hosts.is_empty() {
+            true => vec![None],
+            false => self
+                .config
+                .hosts
+                .iter()
+                .map(|host| Some(host.as_str()))
+                .collect(),
+        }
+    }
+}
+
+impl RunnableJob for StatsJob {
+    type Output = Vec<StatsSample>;
+    type Error = ResticError;
+
+    async fn run(
+        &self,
+        client: &Restic,
+        cancellation_token: &CancellationToken,
+    ) -> Result<Vec<StatsSample>, ResticError> {
+        let mut samples = Vec::new();
+
+        for host in self.get_hosts() {
+            for mode in &self.config.modes {
+                let mut options = StatsOptions::new().mode(to_stats_mode(*mode));
+                if let Some(host) = host {
+                    options = options.host(host);
+                }
+
+                let stats = client.stats(options, cancellation_token).await?;
+                info!(
+                    "Stats {} of {}: {} byte(s) in {} snapshot(s).",
+                    to_stats_mode(*mode),
+                    host.unwrap_or("all hosts"),
+                    stats.total_size,
+                    stats.snapshots_count
+                );
+                samples.push(StatsSample {
+                    job_id: self.job_id.clone(),
+                    taken_at: Local::now(),
+                    mode: *mode,
+                    host: host.map(str::to_owned),
+                    stats,
+                });
+            }
+        }
+
+        Ok(samples)
+    }
+
+    fn get_job_name(&self) -> &str {
+        "Stats"
+    }
+}
+
+fn to_stats_mode(mode: StatsMode) -> stats::StatsMode {
+    match mode {
+        StatsMode::RestoreSize => stats::StatsMode::RestoreSize,
+        StatsMode::FilesByContents => stats::StatsMode::FilesByContents,
+        StatsMode::RawData => stats::StatsMode::RawData,
+        StatsMode::BlobsPerFile => stats::StatsMode::BlobsPerFile,
+    }
+}
@@ -2,13 +2,17 @@
 extern crate windows_service;
 mod api;
 pub(crate) mod cli;
+mod config_status;
+mod effective_config;
 mod host;
 mod jobs;
 pub(crate) mod management;
 pub(crate) mod paths;
+mod runs;
 pub(crate) mod service;
 
 use crate::cli::{Verb, parse_args};
+use crate::effective_config::show_effective_config;
 use crate::host::ServiceHost;
 use crate::management::{
     SERVICE_NAME, install_service, restart_service, start_service, status_service, stop_service,
@@ -71,6 +75,7 @@ async fn main_cli() -> ExitCode {
         Verb::Stop => stop_service(),
         Verb::Restart => restart_service(),
         Verb::Status => status_service(),
+        Verb::ShowEffectiveConfig => return show_effective_config(args.job.as_deref()).await,
         Verb::Run => {
             info!("Running service in CLI mode...");
 
@@ -0,0 +1,276 @@
+use chrono::{DateTime, Local, Utc};
+use restic_sdk::forget::ForgetResult;
+use restic_sdk::messages::{BackupError, DiffStatistics, PruneStats};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// A single run of a job, from being picked up by the worker until all steps finished.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct JobRun {
+    pub run_id: u64,
+    pub job_id: String,
+    #[serde(default)]
+    pub kind: RunKind,
+    pub started_at: DateTime<Local>,
+    pub finished_at: Option<DateTime<Local>>,
+    pub outcome: RunOutcome,
+    /// Why steps of the run failed, in the order they ran.
+    #[serde(default)]
+    pub failures: Vec<String>,
+    /// Problems of optional steps (e.g. the diff with the parent), which do not fail the run.
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    pub backup: Option<BackupRun>,
+    #[serde(default)]
+    pub forget: Option<ForgetRun>,
+    /// The statistics of a prune run, when restic printed them.
+    #[serde(default)]
+    pub prune: Option<PruneStats>,
+    #[serde(skip)]
+    no_sources: bool,
+}
+
+/// What a run does, each kind has its own schedule.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RunKind {
+    /// Clear locks, backup, then forget.
+    #[default]
+    Backup,
+    Prune,
+    Stats,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RunOutcome {
+    Running,
+    Succeeded,
+    Failed,
+    /// The backup found nothing changed and skipped creating a snapshot.
+    Unchanged,
+    /// The backup had nothing to backup, likely a misconfiguration.
+    NoSources,
+    Cancelled,
+    /// The service stopped (e.g. crashed) while the run was running.
+    Interrupted,
+}
+
+/// What the backup step of a run did.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct BackupRun {
+    /// The sources after expansion, as passed to restic.
+    pub sources: Vec<String>,
+    /// The command whose output was backed up instead of sources.
+    #[serde(default)]
+    pub stdin_command: Vec<String>,
+    /// Missing when the backup was skipped because nothing changed, or was a dry run.
+    pub snapshot_id: Option<String>,
+    /// Whether no snapshot was created because nothing changed, a dry run is not unchanged.
+    #[serde(default)]
+    pub unchanged: bool,
+    pub failed_to_read_some_data: bool,
+    /// The number of items restic reported errors for, including the ones not kept in `errors`.
+    #[serde(default)]
+    pub error_count: usize,
+    /// The first errors restic reported for single items, up to `max_errors` of the backup configuration.
+    #[serde(default)]
+    pub errors: Vec<BackupItemError>,
+    /// The file listing the new and modified items, when a manifest is configured.
+    #[serde(default)]
+    pub manifest: Option<PathBuf>,
+    /// The differences between the snapshot and its parent, when `diff_with_parent` is set and there is a parent.
+    #[serde(default)]
+    pub diff: Option<DiffStatistics>,
+}
+
+/// What the forget step of a run did.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ForgetRun {
+    pub kept: Vec<ForgetRunSnapshot>,
+    pub removed: Vec<ForgetRunSnapshot>,
+    /// Missing when prune did not run, or printed no statistics.
+    pub prune: Option<PruneStats>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ForgetRunSnapshot {
+    pub id: String,
+    pub time: DateTime<Utc>,
+    /// The rules of the policy that kept the snapshot.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reasons: Vec<String>,
+}
+
+impl From<ForgetResult> for ForgetRun {
+    fn from(result: ForgetResult) -> Self {
+        Self {
+            kept: result
+                .kept()
+                .map(|(snapshot, reasons)| ForgetRunSnapshot {
+                    id: snapshot.id.clone(),
+                    time: snapshot.time,
+                    reasons: reasons.to_vec(),
+                })
+                .collect(),
+            removed: result
+                .removed()
+                .map(|snapshot| ForgetRunSnapshot {
+                    id: snapshot.id.clone(),
+                    time: snapshot.time,
+                    reasons: Vec::new(),
+                })
+                .collect(),
+            prune: result.prune,
+        }
+    }
+}
+
+/// An error restic reported for a single item, e.g. a file that could not be read.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BackupItemError {
+    /// Usually the path of the file.
+    pub item: String,
+    /// What restic was doing, e.g. "archival".
+    pub during: String,
+    pub message: String,
+}
+
+impl From<BackupError> for BackupItemError {
+    fn from(error: BackupError) -> Self {
+        Self {
+            item: error.item,
+            during: error.during,
+            message: error.error.message,
+        }
+    }
+}
+
+impl JobRun {
+    pub fn new(run_id: u64, job_id: &str, kind: RunKind) -> Self {
+        Self {
+            run_id,
+            job_id: job_id.to_owned(),
+            kind,
+            started_at: Local::now(),
+            finished_at: None,
+            outcome: RunOutcome::Running,
+            failures: Vec::new(),
+            warnings: Vec::new(),
+            backup: None,
+            forget: None,
+            prune: None,
+            no_sources: false,
+        }
+    }
+
+    pub fn add_failure(&mut self, failure: impl Into<String>) {
+        self.failures.push(failure.into());
+    }
+
+    pub fn add_warning(&mut self, warning: impl Into<String>) {
+        self.warnings.push(warning.into());
+    }
+
+    /// Marks the run as having nothing to backup, which takes precedence over other failures.
+    pub fn add_no_sources_failure(&mut self, failure: impl Into<String>) {
+        self.no_sources = true;
+        self.add_failure(failure);
+    }
+
+    /// Completes the run, failed when any step failed.
+    pub fn finish(&mut self, cancelled: bool) {
+        let unchanged = self.backup.as_ref().is_some_and(|backup| backup.unchanged);
+
+        self.finished_at = Some(Local::now());
+        self.outcome = match (cancelled, self.no_sources, self.failures.is_empty()) {
+            (true, _, _) => RunOutcome::Cancelled,
+            (false, true, _) => RunOutcome::NoSources,
+            (false, false, false) => RunOutcome::Failed,
+            (false, false, true) if unchanged => RunOutcome::Unchanged,
+            (false, false, true) => RunOutcome::Succeeded,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finish(run: &mut JobRun, cancelled: bool) -> RunOutcome {
+        run.finish(cancelled);
+        assert!(run.finished_at.is_some());
+        run.outcome
+    }
+
+    fn backup_run(unchanged: bool, failed_to_read_some_data: bool) -> BackupRun {
+        BackupRun {
+            unchanged,
+            failed_to_read_some_data,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn when_no_step_failed_then_succeeded() {
+        let mut run = JobRun::new(1, "job", RunKind::Backup);
+        assert_eq!(run.outcome, RunOutcome::Running);
+
+        run.backup = Some(backup_run(false, false));
+
+        assert_eq!(finish(&mut run, false), RunOutcome::Succeeded);
+    }
+
+    #[test]
+    fn when_some_data_not_read_then_succeeded_with_flag() {
+        let mut run = JobRun::new(1, "job", RunKind::Backup);
+        run.backup = Some(backup_run(false, true));
+
+        assert_eq!(finish(&mut run, false), RunOutcome::Succeeded);
+        assert!(run.backup.unwrap().failed_to_read_some_data);
+    }
+
+    #[test]
+    fn when_backup_unchanged_then_unchanged() {
+        let mut run = JobRun::new(1, "job", RunKind::Backup);
+        run.backup = Some(backup_run(true, false));
+
+        assert_eq!(finish(&mut run, false), RunOutcome::Unchanged);
+    }
+
+    #[test]
+    fn when_step_failed_then_failed() {
+        let mut run = JobRun::new(1, "job", RunKind::Backup);
+        run.backup = Some(backup_run(true, false));
+        run.add_failure("Forget: failed");
+
+        assert_eq!(finish(&mut run, false), RunOutcome::Failed);
+    }
+
+    #[test]
+    fn when_only_warnings_then_backup_outcome_kept() {
+        let mut run = JobRun::new(1, "job", RunKind::Backup);
+        run.backup = Some(backup_run(false, false));
+        run.add_warning("Diff: failed");
+
+        assert_eq!(finish(&mut run, false), RunOutcome::Succeeded);
+        assert_eq!(run.warnings, vec!["Diff: failed"]);
+    }
+
+    #[test]
+    fn when_no_sources_then_no_sources_over_failed() {
+        let mut run = JobRun::new(1, "job", RunKind::Backup);
+        run.add_failure("Clear locks: failed");
+        run.add_no_sources_failure("Backup: no sources");
+
+        assert_eq!(finish(&mut run, false), RunOutcome::NoSources);
+    }
+
+    #[test]
+    fn when_cancelled_then_cancelled_over_everything() {
+        let mut run = JobRun::new(1, "job", RunKind::Backup);
+        run.add_no_sources_failure("Backup: no sources");
+
+        assert_eq!(finish(&mut run, true), RunOutcome::Cancelled);
+    }
+}
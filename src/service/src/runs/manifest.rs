@@ -0,0 +1,189 @@
+use common::config::ManifestFormat;
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use log::warn;
+use restic_sdk::messages::{BackupVerboseStatus, BackupVerboseStatusAction};
+use serde::Serialize;
+use std::fs::{File, create_dir_all};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::mpsc::{Receiver, Sender, channel};
+use tokio::task::{JoinHandle, block_in_place, spawn_blocking};
+
+/// Number of items queued for the writer, once full the backup waits for the disk.
+const MANIFEST_QUEUE_SIZE: usize = 4096;
+
+/// Writes the new and modified items of a backup as JSON lines, while the backup runs.
+///
+/// Items are passed to a blocking task that writes the file, so the backup only waits for the disk when the queue is
+/// full.
+pub struct ManifestWriter {
+    path: PathBuf,
+    sender: Sender<ManifestEntry>,
+    writer: JoinHandle<io::Result<()>>,
+}
+
+enum ManifestOutput {
+    Plain(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+}
+
+#[derive(Serialize)]
+struct ManifestEntry {
+    action: &'static str,
+    item: String,
+    /// Size of the item in bytes.
+    size: u64,
+}
+
+impl ManifestWriter {
+    pub async fn create(path: &Path, format: ManifestFormat) -> io::Result<Self> {
+        let output = {
+            let path = path.to_owned();
+            spawn_blocking(move || ManifestOutput::create(&path, format)).await??
+        };
+        let (sender, receiver) = channel(MANIFEST_QUEUE_SIZE);
+
+        Ok(Self {
+            path: path.to_owned(),
+            sender,
+            writer: spawn_blocking(move || output.write_entries(receiver)),
+        })
+    }
+
+    /// Queues the item when it is new or modified, the writer stops after the first failure.
+    ///
+    /// Blocks the thread while the queue is full, which needs the multi-threaded runtime.
+    pub fn write(&self, status: &BackupVerboseStatus) {
+        let action = match status.action {
+            BackupVerboseStatusAction::New => "new",
+            BackupVerboseStatusAction::Modified => "modified",
+            BackupVerboseStatusAction::Unchanged | BackupVerboseStatusAction::ScanFinished => {
+                return;
+            }
+        };
+
+        let entry = ManifestEntry {
+            action,
+            item: status.item.clone(),
+            size: status.data_size,
+        };
+        // Sending only fails once the writer failed, which finish reports.
+        if let Err(TrySendError::Full(entry)) = self.sender.try_send(entry) {
+            // Restic is not read meanwhile, so it slows down to the pace of the writer.
+            _ = block_in_place(|| self.sender.blocking_send(entry));
+        }
+    }
+
+    /// Waits for the queued items to be written, returning the path when the manifest is complete.
+    pub async fn finish(self) -> Option<PathBuf> {
+        drop(self.sender);
+        let result = match self.writer.await {
+            Ok(result) => result,
+            Err(e) => Err(e.into()),
+        };
+
+        match result {
+            Ok(_) => Some(self.path),
+            Err(e) => {
+                warn!(
+                    "Failed to write manifest '{}', it is incomplete. Error: {e}",
+                    self.path.display()
+                );
+                None
+            }
+        }
+    }
+}
+
+impl ManifestOutput {
+    fn create(path: &Path, format: ManifestFormat) -> io::Result<Self> {
+        if let Some(directory) = path.parent() {
+            create_dir_all(directory)?;
+        }
+
+        let file = BufWriter::new(File::create(path)?);
+        Ok(match format {
+            ManifestFormat::Plain => ManifestOutput::Plain(file),
+            ManifestFormat::Gzip => {
+                ManifestOutput::Gzip(GzEncoder::new(file, Compression::default()))
+            }
+        })
+    }
+
+    /// Writes the entries until the channel is closed, then flushes the file.
+    fn write_entries(mut self, mut receiver: Receiver<ManifestEntry>) -> io::Result<()> {
+        while let Some(entry) = receiver.blocking_recv() {
+            serde_json::to_writer(&mut self, &entry)?;
+            self.write_all(b"\n")?;
+        }
+
+        match self {
+            ManifestOutput::Plain(mut file) => file.flush(),
+            ManifestOutput::Gzip(encoder) => encoder.finish()?.flush(),
+        }
+    }
+}
+
+impl Write for ManifestOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            ManifestOutput::Plain(file) => file.write(buf),
+            ManifestOutput::Gzip(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            ManifestOutput::Plain(file) => file.flush(),
+            ManifestOutput::Gzip(encoder) => encoder.flush(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(action: BackupVerboseStatusAction, item: String) -> BackupVerboseStatus {
+        BackupVerboseStatus {
+            action,
+            item,
+            duration_seconds: 0.0,
+            data_size: 1,
+            data_size_in_repo: 1,
+            metadata_size: 0,
+            metadata_size_in_repo: 0,
+            total_files: 1,
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn when_queue_is_full_then_every_item_is_written() {
+        let path = std::env::temp_dir().join(format!(
+            "restic-service-manifest-{}.jsonl",
+            std::process::id()
+        ));
+        let manifest = ManifestWriter::create(&path, ManifestFormat::Plain)
+            .await
+            .unwrap();
+
+        for index in 0..MANIFEST_QUEUE_SIZE * 3 {
+            manifest.write(&status(BackupVerboseStatusAction::New, index.to_string()));
+            manifest.write(&status(
+                BackupVerboseStatusAction::Unchanged,
+                index.to_string(),
+            ));
+        }
+        let written = manifest.finish().await.unwrap();
+        let content = std::fs::read_to_string(&written).unwrap();
+        _ = std::fs::remove_file(&written);
+
+        assert_eq!(content.lines().count(), MANIFEST_QUEUE_SIZE * 3);
+        assert_eq!(
+            content.lines().next(),
+            Some(r#"{"action":"new","item":"0","size":1}"#)
+        );
+    }
+}
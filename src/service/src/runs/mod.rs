@@ -0,0 +1,11 @@
+mod job_run;
+mod manifest;
+mod run_history;
+mod stats_history;
+mod stats_summary;
+
+pub use job_run::*;
+pub use manifest::*;
+pub use run_history::*;
+pub use stats_history::*;
+pub use stats_summary::*;
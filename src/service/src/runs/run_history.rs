@@ -0,0 +1,276 @@
+use crate::runs::{JobRun, RunKind, RunOutcome};
+use common::config::ManifestFormat;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use tokio::fs::{read_to_string, remove_file, rename, write};
+
+/// Number of runs kept per job, older runs are dropped.
+const MAX_RUNS_PER_JOB: usize = 50;
+
+/// Name of the directory next to the history the manifests of runs are written to.
+const MANIFESTS_DIRECTORY: &str = "manifests";
+
+/// The recent runs of all jobs, persisted as JSON so they survive restarts.
+pub struct RunHistory {
+    path: PathBuf,
+    inner: RwLock<RunHistoryState>,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+struct RunHistoryState {
+    next_run_id: u64,
+    /// Ordered by run id, oldest first.
+    runs: Vec<JobRun>,
+}
+
+impl RunHistory {
+    /// Loads the history from the path, starting empty when it is missing or unreadable.
+    pub async fn load(path: PathBuf) -> Self {
+        let mut state = match read_to_string(&path).await {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                warn!(
+                    "Failed to parse run history '{}', starting with an empty history. Error: {e}",
+                    path.display()
+                );
+                RunHistoryState::default()
+            }),
+            Err(e) => {
+                debug!(
+                    "No run history read from '{}', starting with an empty history. Error: {e}",
+                    path.display()
+                );
+                RunHistoryState::default()
+            }
+        };
+        state.mark_interrupted();
+
+        Self {
+            path,
+            inner: RwLock::new(state),
+        }
+    }
+
+    /// Creates a new running run for the job, visible until it is finished.
+    pub fn start(&self, job_id: &str, kind: RunKind) -> JobRun {
+        let mut state = self.inner.write().unwrap();
+        state.next_run_id += 1;
+        let run = JobRun::new(state.next_run_id, job_id, kind);
+        state.runs.push(run.clone());
+        run
+    }
+
+    /// Replaces the run with its final state, then persists the history.
+    pub async fn finish(&self, run: JobRun) {
+        let (json, dropped) = {
+            let mut state = self.inner.write().unwrap();
+            match state.runs.iter_mut().find(|x| x.run_id == run.run_id) {
+                Some(existing) => *existing = run,
+                None => state.runs.push(run),
+            }
+            let dropped = state.prune();
+            (serde_json::to_string(&*state), dropped)
+        };
+
+        // Manifests are kept as long as their run.
+        for manifest in dropped
+            .iter()
+            .filter_map(|run| run.backup.as_ref()?.manifest.as_ref())
+        {
+            if let Err(e) = remove_file(manifest).await {
+                debug!(
+                    "Failed to remove manifest '{}'. Error: {e}",
+                    manifest.display()
+                );
+            }
+        }
+
+        match json {
+            Ok(json) => {
+                if let Err(e) = self.persist(json).await {
+                    warn!(
+                        "Failed to write run history '{}'. Error: {e}",
+                        self.path.display()
+                    );
+                }
+            }
+            Err(e) => warn!("Failed to serialize run history. Error: {e}"),
+        }
+    }
+
+    /// The runs of the job, most recent first.
+    pub fn get_runs(&self, job_id: &str) -> Vec<JobRun> {
+        let state = self.inner.read().unwrap();
+        state
+            .runs
+            .iter()
+            .rev()
+            .filter(|run| run.job_id == job_id)
+            .cloned()
+            .collect()
+    }
+
+    pub fn get_run(&self, job_id: &str, run_id: u64) -> Option<JobRun> {
+        let state = self.inner.read().unwrap();
+        state
+            .runs
+            .iter()
+            .find(|run| run.job_id == job_id && run.run_id == run_id)
+            .cloned()
+    }
+
+    /// Where the manifest of the run is written to.
+    pub fn get_manifest_path(&self, run: &JobRun, format: ManifestFormat) -> PathBuf {
+        let job_id: String = run
+            .job_id
+            .chars()
+            .map(
+                |c| match c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    true => c,
+                    false => '_',
+                },
+            )
+            .collect();
+        let extension = match format {
+            ManifestFormat::Plain => "jsonl",
+            ManifestFormat::Gzip => "jsonl.gz",
+        };
+
+        self.path
+            .with_file_name(MANIFESTS_DIRECTORY)
+            .join(format!("{}-{job_id}.{extension}", run.run_id))
+    }
+
+    async fn persist(&self, json: String) -> std::io::Result<()> {
+        // Replace the file in one step, so a crash never leaves a partial history.
+        let temporary_path = self.path.with_extension("json.tmp");
+        write(&temporary_path, json).await?;
+        rename(&temporary_path, &self.path).await
+    }
+}
+
+impl RunHistoryState {
+    /// Marks the runs a previous service left running, they never finish otherwise.
+    fn mark_interrupted(&mut self) {
+        for run in self
+            .runs
+            .iter_mut()
+            .filter(|run| run.outcome == RunOutcome::Running)
+        {
+            warn!(
+                "Run {} of job '{}' was interrupted by the service stopping.",
+                run.run_id, run.job_id
+            );
+            run.outcome = RunOutcome::Interrupted;
+        }
+    }
+
+    /// Drops the oldest runs over the maximum per job, returning them.
+    fn prune(&mut self) -> Vec<JobRun> {
+        let mut counts = HashMap::<&str, usize>::new();
+        let mut keep = vec![true; self.runs.len()];
+        for (index, run) in self.runs.iter().enumerate().rev() {
+            let count = counts.entry(run.job_id.as_str()).or_default();
+            *count += 1;
+            keep[index] = *count <= MAX_RUNS_PER_JOB;
+        }
+
+        let mut dropped = Vec::new();
+        let mut keep = keep.into_iter();
+        self.runs.retain(|run| {
+            let kept = keep.next().unwrap_or(true);
+            if !kept {
+                dropped.push(run.clone());
+            }
+            kept
+        });
+        dropped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_history_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("restic-service-{name}-{}.json", std::process::id()))
+    }
+
+    fn state_with_runs(job_ids: &[&str]) -> RunHistoryState {
+        let runs: Vec<_> = job_ids
+            .iter()
+            .enumerate()
+            .map(|(index, job_id)| JobRun::new(index as u64 + 1, job_id, RunKind::Backup))
+            .collect();
+        RunHistoryState {
+            next_run_id: runs.len() as u64,
+            runs,
+        }
+    }
+
+    #[test]
+    fn when_over_maximum_then_oldest_runs_of_job_dropped() {
+        let mut job_ids = vec!["a"; MAX_RUNS_PER_JOB + 2];
+        job_ids.insert(1, "b");
+        let mut state = state_with_runs(&job_ids);
+
+        let dropped = state.prune();
+
+        let dropped_ids: Vec<_> = dropped.iter().map(|run| run.run_id).collect();
+        assert_eq!(dropped_ids, vec![1, 3]);
+        assert_eq!(state.runs.len(), MAX_RUNS_PER_JOB + 1);
+        assert!(state.runs.iter().any(|run| run.job_id == "b"));
+    }
+
+    #[test]
+    fn when_under_maximum_then_nothing_dropped() {
+        let mut state = state_with_runs(&["a", "b", "a"]);
+
+        assert!(state.prune().is_empty());
+        assert_eq!(state.runs.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn runs_are_listed_per_job_most_recent_first() {
+        let path = get_history_path("run-history-list");
+        let history = RunHistory::load(path.clone()).await;
+
+        for job_id in ["a", "b", "a"] {
+            let mut run = history.start(job_id, RunKind::Backup);
+            run.finish(false);
+            history.finish(run).await;
+        }
+        let reloaded = RunHistory::load(path.clone()).await;
+        _ = std::fs::remove_file(&path);
+
+        let run_ids: Vec<_> = reloaded
+            .get_runs("a")
+            .iter()
+            .map(|run| run.run_id)
+            .collect();
+        assert_eq!(run_ids, vec![3, 1]);
+        assert_eq!(
+            reloaded.get_run("b", 2).unwrap().outcome,
+            RunOutcome::Succeeded
+        );
+        assert!(reloaded.get_run("a", 2).is_none());
+    }
+
+    #[tokio::test]
+    async fn when_loaded_with_running_run_then_interrupted() {
+        let path = get_history_path("run-history-interrupted");
+        let state = state_with_runs(&["a"]);
+        std::fs::write(&path, serde_json::to_string(&state).unwrap()).unwrap();
+
+        let history = RunHistory::load(path.clone()).await;
+        _ = std::fs::remove_file(&path);
+
+        assert_eq!(
+            history.get_run("a", 1).unwrap().outcome,
+            RunOutcome::Interrupted
+        );
+        assert_eq!(history.start("a", RunKind::Backup).run_id, 2);
+    }
+}
@@ -0,0 +1,102 @@
+use chrono::{DateTime, Local};
+use common::config::StatsMode;
+use log::{debug, warn};
+use restic_sdk::messages::RepositoryStats;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::RwLock;
+use tokio::fs::{read_to_string, rename, write};
+
+/// Number of samples kept per job, enough for years of daily statistics in a few modes.
+const MAX_SAMPLES_PER_JOB: usize = 5000;
+
+/// The statistics of a repository taken once, by one mode for one host (or all of them).
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StatsSample {
+    pub job_id: String,
+    pub taken_at: DateTime<Local>,
+    pub mode: StatsMode,
+    /// The host the statistics are filtered by, `None` for the whole repository.
+    pub host: Option<String>,
+    pub stats: RepositoryStats,
+}
+
+/// The statistics taken by the stats runs of all jobs, kept apart from the run history so it can grow longer.
+pub struct StatsHistory {
+    path: PathBuf,
+    inner: RwLock<Vec<StatsSample>>,
+}
+
+impl StatsHistory {
+    /// Loads the history from the path, starting empty when it is missing or unreadable.
+    pub async fn load(path: PathBuf) -> Self {
+        let samples = match read_to_string(&path).await {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                warn!(
+                    "Failed to parse stats history '{}', starting with an empty history. Error: {e}",
+                    path.display()
+                );
+                Vec::new()
+            }),
+            Err(e) => {
+                debug!(
+                    "No stats history read from '{}', starting with an empty history. Error: {e}",
+                    path.display()
+                );
+                Vec::new()
+            }
+        };
+
+        Self {
+            path,
+            inner: RwLock::new(samples),
+        }
+    }
+
+    /// Adds the samples, dropping the oldest ones over the maximum of their job, then persists the history.
+    pub async fn add(&self, samples: Vec<StatsSample>) {
+        let json = {
+            let mut state = self.inner.write().unwrap();
+            for sample in samples {
+                let job_samples = state.iter().filter(|x| x.job_id == sample.job_id).count();
+                if job_samples >= MAX_SAMPLES_PER_JOB
+                    && let Some(oldest) = state.iter().position(|x| x.job_id == sample.job_id)
+                {
+                    state.remove(oldest);
+                }
+                state.push(sample);
+            }
+            serde_json::to_string(&*state)
+        };
+
+        match json {
+            Ok(json) => {
+                if let Err(e) = self.persist(json).await {
+                    warn!(
+                        "Failed to write stats history '{}'. Error: {e}",
+                        self.path.display()
+                    );
+                }
+            }
+            Err(e) => warn!("Failed to serialize stats history. Error: {e}"),
+        }
+    }
+
+    /// The samples of the job, most recent first.
+    pub fn get_samples(&self, job_id: &str) -> Vec<StatsSample> {
+        let state = self.inner.read().unwrap();
+        state
+            .iter()
+            .rev()
+            .filter(|sample| sample.job_id == job_id)
+            .cloned()
+            .collect()
+    }
+
+    async fn persist(&self, json: String) -> std::io::Result<()> {
+        // Replace the file in one step, so a crash never leaves a partial history.
+        let temporary_path = self.path.with_extension("json.tmp");
+        write(&temporary_path, json).await?;
+        rename(&temporary_path, &self.path).await
+    }
+}
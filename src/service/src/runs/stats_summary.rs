@@ -0,0 +1,166 @@
+use crate::runs::StatsSample;
+use chrono::{DateTime, Local};
+use common::config::StatsMode;
+use serde::Serialize;
+
+/// The latest `restore-size` and `raw-data` statistics of a host, `None` for the whole repository.
+#[derive(Serialize)]
+pub struct HostStatsSummary {
+    host: Option<String>,
+    taken_at: DateTime<Local>,
+    /// Size in bytes of all snapshots when restored
+    restore_size: Option<u64>,
+    /// Size in bytes stored in the repository
+    raw_data_size: Option<u64>,
+    /// Restore size divided by the stored size before compression, how much deduplication saves
+    dedup_ratio: Option<f64>,
+    compression_ratio: Option<f64>,
+    #[serde(skip)]
+    uncompressed_size: Option<u64>,
+}
+
+impl HostStatsSummary {
+    /// Summarizes the most recent samples, which come first.
+    pub fn from_latest(samples: &[StatsSample]) -> Vec<Self> {
+        let mut summaries: Vec<HostStatsSummary> = Vec::new();
+
+        for sample in samples {
+            let index = match summaries.iter().position(|x| x.host == sample.host) {
+                Some(index) => index,
+                None => {
+                    summaries.push(HostStatsSummary {
+                        host: sample.host.clone(),
+                        taken_at: sample.taken_at,
+                        restore_size: None,
+                        raw_data_size: None,
+                        dedup_ratio: None,
+                        compression_ratio: None,
+                        uncompressed_size: None,
+                    });
+                    summaries.len() - 1
+                }
+            };
+            let summary = &mut summaries[index];
+
+            match sample.mode {
+                StatsMode::RestoreSize if summary.restore_size.is_none() => {
+                    summary.restore_size = Some(sample.stats.total_size);
+                }
+                StatsMode::RawData if summary.raw_data_size.is_none() => {
+                    summary.raw_data_size = Some(sample.stats.total_size);
+                    summary.compression_ratio = sample.stats.compression_ratio;
+                    summary.uncompressed_size = sample
+                        .stats
+                        .total_uncompressed_size
+                        .or(Some(sample.stats.total_size));
+                }
+                _ => {}
+            }
+        }
+
+        for summary in &mut summaries {
+            summary.dedup_ratio = match (summary.restore_size, summary.uncompressed_size) {
+                (Some(restore_size), Some(uncompressed_size)) if uncompressed_size > 0 => {
+                    Some(restore_size as f64 / uncompressed_size as f64)
+                }
+                _ => None,
+            };
+        }
+
+        summaries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use restic_sdk::messages::RepositoryStats;
+
+    fn sample(
+        minute: u32,
+        host: Option<&str>,
+        mode: StatsMode,
+        stats: RepositoryStats,
+    ) -> StatsSample {
+        StatsSample {
+            job_id: "job".to_owned(),
+            taken_at: Local.with_ymd_and_hms(2025, 1, 1, 0, minute, 0).unwrap(),
+            mode,
+            host: host.map(str::to_owned),
+            stats,
+        }
+    }
+
+    fn restore_size(size: u64) -> RepositoryStats {
+        RepositoryStats {
+            total_size: size,
+            ..Default::default()
+        }
+    }
+
+    fn raw_data(size: u64, uncompressed_size: Option<u64>) -> RepositoryStats {
+        RepositoryStats {
+            total_size: size,
+            total_uncompressed_size: uncompressed_size,
+            compression_ratio: uncompressed_size.map(|x| x as f64 / size as f64),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn summarizes_latest_sample_of_each_mode_per_host() {
+        // Most recent first, as the history returns them.
+        let samples = vec![
+            sample(5, Some("a"), StatsMode::RawData, raw_data(100, Some(200))),
+            sample(4, Some("b"), StatsMode::RestoreSize, restore_size(300)),
+            sample(3, Some("a"), StatsMode::FilesByContents, restore_size(999)),
+            sample(2, Some("a"), StatsMode::RestoreSize, restore_size(800)),
+            sample(1, Some("a"), StatsMode::RestoreSize, restore_size(1)),
+            sample(0, Some("a"), StatsMode::RawData, raw_data(1, Some(1))),
+        ];
+
+        let summaries = HostStatsSummary::from_latest(&samples);
+
+        assert_eq!(summaries.len(), 2);
+        let a = &summaries[0];
+        assert_eq!(a.host.as_deref(), Some("a"));
+        assert_eq!(a.taken_at, samples[0].taken_at);
+        assert_eq!(a.restore_size, Some(800));
+        assert_eq!(a.raw_data_size, Some(100));
+        assert_eq!(a.compression_ratio, Some(2.0));
+        assert_eq!(a.dedup_ratio, Some(4.0));
+        let b = &summaries[1];
+        assert_eq!(b.host.as_deref(), Some("b"));
+        assert_eq!(b.restore_size, Some(300));
+        assert_eq!(b.raw_data_size, None);
+        assert_eq!(b.dedup_ratio, None);
+    }
+
+    #[test]
+    fn dedup_ratio_uses_stored_size_without_compression_info() {
+        let samples = vec![
+            sample(1, None, StatsMode::RestoreSize, restore_size(500)),
+            sample(0, None, StatsMode::RawData, raw_data(250, None)),
+        ];
+
+        let summaries = HostStatsSummary::from_latest(&samples);
+
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].host, None);
+        assert_eq!(summaries[0].compression_ratio, None);
+        assert_eq!(summaries[0].dedup_ratio, Some(2.0));
+    }
+
+    #[test]
+    fn dedup_ratio_missing_for_empty_repository() {
+        let samples = vec![
+            sample(1, None, StatsMode::RestoreSize, restore_size(0)),
+            sample(0, None, StatsMode::RawData, raw_data(0, Some(0))),
+        ];
+
+        let summaries = HostStatsSummary::from_latest(&samples);
+
+        assert_eq!(summaries[0].dedup_ratio, None);
+    }
+}